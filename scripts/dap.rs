@@ -0,0 +1,341 @@
+//! Debug Adapter Protocol server for Egg scripts, speaking the protocol over stdio.
+//! Supports launching a script, line breakpoints, stack traces of Egg function calls, variable scopes and stepping.
+use collections::{BTreeMap, BTreeSet, VecDeque};
+use egglang::{
+	debugger::{self, Debugger, Frontend, PauseReason, Paused, Step},
+	error::EggResult,
+	evaluator::evaluate,
+	expression::Value,
+	io::{InputBuffer, Output},
+	operators::{self, Operator},
+	parser::{self, LineIndex},
+	scope::Scope,
+};
+use io::{BufRead, Read, Write};
+use serde_json::{Value as Json, json};
+use std::*;
+use sync::{
+	atomic::{AtomicU64, Ordering},
+	mpsc::{self, Receiver},
+};
+
+const LOCALS: u64 = 1;
+const GLOBALS: u64 = 2;
+/// Objects are exposed as expandable variables, with references offset by this amount
+const OBJECTS: u64 = 3;
+
+static SEQUENCE: AtomicU64 = AtomicU64::new(1);
+
+fn send(mut message: Json) {
+	message["seq"] = SEQUENCE.fetch_add(1, Ordering::Relaxed).into();
+	let body = message.to_string();
+
+	let mut stdout = io::stdout().lock();
+	write!(stdout, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+	stdout.flush().unwrap();
+}
+
+fn respond(request: &Json, body: Json) {
+	send(json!({ "type": "response", "request_seq": request["seq"], "command": request["command"], "success": true, "body": body }));
+}
+
+fn fail(request: &Json, message: &str) {
+	send(json!({ "type": "response", "request_seq": request["seq"], "command": request["command"], "success": false, "message": message }));
+}
+
+fn event(name: &str, body: Json) {
+	send(json!({ "type": "event", "event": name, "body": body }));
+}
+
+/// Read messages from stdin on a background thread, so requests can be polled while the script runs
+fn spawn_reader() -> Receiver<Json> {
+	let (sender, receiver) = mpsc::channel();
+
+	thread::spawn(move || {
+		let mut stdin = io::stdin().lock();
+
+		loop {
+			let mut length = None;
+			loop {
+				let mut header = String::new();
+				if stdin.read_line(&mut header).unwrap_or(0) == 0 {
+					return;
+				}
+
+				match header.trim() {
+					"" => break,
+					header => {
+						if let Some(value) = header.strip_prefix("Content-Length:") {
+							length = value.trim().parse::<usize>().ok();
+						}
+					}
+				}
+			}
+
+			let mut body = vec![0; length.unwrap_or(0)];
+			if stdin.read_exact(&mut body).is_err() {
+				return;
+			}
+
+			let Ok(message) = serde_json::from_slice(&body) else { return };
+			if sender.send(message).is_err() {
+				return;
+			}
+		}
+	});
+
+	receiver
+}
+
+fn breakpoint_lines(request: &Json) -> BTreeSet<usize> {
+	let breakpoints = request["arguments"]["breakpoints"].as_array();
+	breakpoints.into_iter().flatten().filter_map(|b| b["line"].as_u64()).map(|line| line as usize).collect()
+}
+
+fn verify_breakpoints(request: &Json, lines: &BTreeSet<usize>) {
+	let breakpoints = lines.iter().map(|line| json!({ "verified": true, "line": line })).collect::<Vec<_>>();
+	respond(request, json!({ "breakpoints": breakpoints }));
+}
+
+/// Prints to the debug console using `output` events, as stdout carries the protocol
+struct DebugConsole;
+
+impl Output for DebugConsole {
+	fn write(&mut self, text: &str) -> EggResult<()> {
+		event("output", json!({ "category": "stdout", "output": text }));
+		Ok(())
+	}
+}
+
+struct Session {
+	program: String,
+	index: LineIndex,
+	requests: Receiver<Json>,
+	/// Requests received while running, that can only be answered once paused
+	deferred: VecDeque<Json>,
+	operators: BTreeMap<&'static str, Box<dyn Operator>>,
+}
+
+impl Session {
+	fn variables(&self, paused: &Paused<'_>, reference: u64) -> Vec<Json> {
+		let bindings = match reference {
+			LOCALS => paused.locals(),
+			GLOBALS => paused.globals(),
+			tag => match paused.scope.get_object_tag(Value::Object((tag - OBJECTS) as usize)) {
+				Ok(tag) => paused.scope.get_object(tag).iter().map(|(k, v)| (k.to_string().into(), v.clone())).collect(),
+				Err(_) => Vec::new(),
+			},
+		};
+
+		bindings
+			.into_iter()
+			.map(|(name, value)| {
+				let reference = match value {
+					Value::Object(tag) => tag as u64 + OBJECTS,
+					_ => 0,
+				};
+
+				json!({ "name": name.as_str(), "value": debugger::describe(&value, paused.scope), "variablesReference": reference })
+			})
+			.collect()
+	}
+
+	fn stack_trace(&self, paused: &Paused<'_>) -> Json {
+		let frames = paused
+			.stack
+			.iter()
+			.enumerate()
+			.rev()
+			.map(|(id, frame)| {
+				let (line, column) = self.index.location(frame.span.start);
+				json!({ "id": id, "name": frame.name.as_str(), "line": line, "column": column, "source": { "path": self.program } })
+			})
+			.collect::<Vec<_>>();
+
+		json!({ "stackFrames": frames, "totalFrames": frames.len() })
+	}
+}
+
+impl Frontend for Session {
+	fn paused(&mut self, paused: &mut Paused<'_>) -> Step {
+		let reason = match paused.reason {
+			PauseReason::Entry => "entry",
+			PauseReason::Breakpoint => "breakpoint",
+			PauseReason::Step => "step",
+			PauseReason::Request => "pause",
+		};
+		event("stopped", json!({ "reason": reason, "threadId": 1, "allThreadsStopped": true }));
+
+		while let Some(request) = self.deferred.pop_front().or_else(|| self.requests.recv().ok()) {
+			match request["command"].as_str().unwrap_or_default() {
+				"threads" => respond(&request, json!({ "threads": [{ "id": 1, "name": "main" }] })),
+				"stackTrace" => respond(&request, self.stack_trace(paused)),
+				"scopes" => {
+					let scopes = json!([
+						{ "name": "Locals", "presentationHint": "locals", "variablesReference": LOCALS, "expensive": false },
+						{ "name": "Globals", "variablesReference": GLOBALS, "expensive": false },
+					]);
+					respond(&request, json!({ "scopes": scopes }))
+				}
+				"variables" => {
+					let reference = request["arguments"]["variablesReference"].as_u64().unwrap_or(0);
+					respond(&request, json!({ "variables": self.variables(paused, reference) }))
+				}
+				"evaluate" => {
+					let expression = request["arguments"]["expression"].as_str().unwrap_or_default();
+					match parser::parse(expression, &self.operators).and_then(|exprs| paused.evaluate(&exprs)) {
+						Ok(value) => respond(&request, json!({ "result": debugger::describe(&value, paused.scope), "variablesReference": 0 })),
+						Err(err) => fail(&request, &err.to_string()),
+					}
+				}
+				"setBreakpoints" => {
+					*paused.breakpoints = breakpoint_lines(&request);
+					verify_breakpoints(&request, paused.breakpoints);
+				}
+				command @ ("continue" | "next" | "stepIn" | "stepOut") => {
+					respond(&request, json!({ "allThreadsContinued": true }));
+					return match command {
+						"next" => Step::StepOver,
+						"stepIn" => Step::StepIn,
+						"stepOut" => Step::StepOut,
+						_ => Step::Continue,
+					};
+				}
+				"disconnect" | "terminate" => {
+					respond(&request, json!({}));
+					process::exit(0);
+				}
+				_ => respond(&request, json!({})),
+			}
+		}
+
+		// The client hung up
+		process::exit(0)
+	}
+
+	fn running(&mut self, breakpoints: &mut BTreeSet<usize>) -> bool {
+		let mut pause = false;
+
+		while let Ok(request) = self.requests.try_recv() {
+			match request["command"].as_str().unwrap_or_default() {
+				"setBreakpoints" => {
+					*breakpoints = breakpoint_lines(&request);
+					verify_breakpoints(&request, breakpoints);
+				}
+				"threads" => respond(&request, json!({ "threads": [{ "id": 1, "name": "main" }] })),
+				"pause" => {
+					respond(&request, json!({}));
+					pause = true;
+				}
+				"disconnect" | "terminate" => {
+					respond(&request, json!({}));
+					process::exit(0);
+				}
+				_ => self.deferred.push_back(request),
+			}
+		}
+
+		pause
+	}
+}
+
+fn main() {
+	let requests = spawn_reader();
+
+	// Configure the session, until the client has launched a program and sent it's breakpoints
+	let mut program = None;
+	let mut stop_on_entry = false;
+	let mut breakpoints = BTreeSet::new();
+	let mut configured = false;
+
+	while program.is_none() || !configured {
+		let Ok(request) = requests.recv() else { return };
+
+		match request["command"].as_str().unwrap_or_default() {
+			"initialize" => {
+				respond(&request, json!({ "supportsConfigurationDoneRequest": true, "supportsTerminateRequest": true }));
+				event("initialized", json!({}));
+			}
+			"launch" => {
+				let arguments = &request["arguments"];
+				match arguments["program"].as_str() {
+					Some(path) => {
+						program = Some(path.to_string());
+						stop_on_entry = arguments["stopOnEntry"].as_bool().unwrap_or(false);
+						respond(&request, json!({}));
+					}
+					None => fail(&request, "launch requires a 'program' to debug"),
+				}
+			}
+			"setBreakpoints" => {
+				breakpoints = breakpoint_lines(&request);
+				verify_breakpoints(&request, &breakpoints);
+			}
+			"configurationDone" => {
+				configured = true;
+				respond(&request, json!({}));
+			}
+			"threads" => respond(&request, json!({ "threads": [{ "id": 1, "name": "main" }] })),
+			"disconnect" | "terminate" => return respond(&request, json!({})),
+			_ => respond(&request, json!({})),
+		}
+	}
+
+	let program = program.unwrap_or_default();
+	let exit_code = match fs::read_to_string(&program) {
+		Ok(source) => run(program, source, requests, stop_on_entry, breakpoints),
+		Err(err) => {
+			event("output", json!({ "category": "stderr", "output": format!("Unable to read {program}: {err}\n") }));
+			1
+		}
+	};
+
+	event("exited", json!({ "exitCode": exit_code }));
+	event("terminated", json!({}));
+}
+
+fn run(program: String, source: String, requests: Receiver<Json>, stop_on_entry: bool, breakpoints: BTreeSet<usize>) -> i32 {
+	let mut operators = operators::empty();
+	operators::minimal(&mut operators);
+	operators::objects(&mut operators);
+	operators::strings(&mut operators);
+	operators::console(&mut operators);
+
+	let expressions = match parser::parse(&source, &operators) {
+		Ok(expressions) => expressions,
+		Err(err) => {
+			event("output", json!({ "category": "stderr", "output": format!("{err}\n") }));
+			return 1;
+		}
+	};
+
+	let mut session = Session {
+		program,
+		index: LineIndex::new(&source),
+		requests,
+		deferred: VecDeque::new(),
+		operators: operators::empty(),
+	};
+	operators::minimal(&mut session.operators);
+	operators::objects(&mut session.operators);
+	operators::strings(&mut session.operators);
+
+	let mut debugger = Debugger::new(&source, session);
+	debugger.stop_on_entry(stop_on_entry);
+	breakpoints.into_iter().for_each(|line| debugger.add_breakpoint(line));
+
+	let mut scope = Scope::default();
+	scope.add_hook(Box::new(debugger));
+	// Stdin carries the protocol too, so there is no input to read
+	scope.set_output(Box::new(DebugConsole));
+	scope.set_input(Box::new(InputBuffer::default()));
+
+	for expression in &expressions {
+		if let Err(err) = evaluate(expression, &mut scope) {
+			event("output", json!({ "category": "stderr", "output": format!("{err}\n") }));
+			return 1;
+		}
+	}
+
+	0
+}
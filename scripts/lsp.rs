@@ -0,0 +1,376 @@
+//! Language Server Protocol server for Egg scripts, speaking the protocol over stdio.
+//! Provides diagnostics from the parser and linter, hover documentation for builtins, go-to-definition, completion and document symbols.
+use collections::BTreeMap;
+use egglang::{
+	docs,
+	expression::{Expression, Function, Visitor, walk_expression},
+	lint::Linter,
+	operators::{self, Operator},
+	parser::{self, LineIndex},
+	scope::Scope,
+};
+use io::{BufRead, Write};
+use serde_json::{Value as Json, json};
+use std::*;
+
+const SYMBOL_FUNCTION: u64 = 12;
+const SYMBOL_VARIABLE: u64 = 13;
+const COMPLETION_FUNCTION: u64 = 3;
+const COMPLETION_VARIABLE: u64 = 6;
+const SEVERITY_ERROR: u64 = 1;
+const SEVERITY_WARNING: u64 = 2;
+
+fn send(message: Json) {
+	let body = message.to_string();
+
+	let mut stdout = io::stdout().lock();
+	write!(stdout, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+	stdout.flush().unwrap();
+}
+
+fn receive(stdin: &mut impl BufRead) -> Option<Json> {
+	let mut length = None;
+	loop {
+		let mut header = String::new();
+		if stdin.read_line(&mut header).ok()? == 0 {
+			return None;
+		}
+
+		match header.trim() {
+			"" => break,
+			header => {
+				if let Some(value) = header.strip_prefix("Content-Length:") {
+					length = value.trim().parse::<usize>().ok();
+				}
+			}
+		}
+	}
+
+	let mut body = vec![0; length?];
+	stdin.read_exact(&mut body).ok()?;
+	serde_json::from_slice(&body).ok()
+}
+
+/// A text document open in the editor
+struct Document {
+	source: String,
+	index: LineIndex,
+}
+
+impl Document {
+	fn new(source: String) -> Document {
+		Document {
+			index: LineIndex::new(&source),
+			source,
+		}
+	}
+
+	/// Convert a byte offset into an LSP position, with 0-based lines and UTF-16 columns
+	fn position(&self, offset: usize) -> Json {
+		let offset = offset.min(self.source.len());
+		let (line, column) = self.index.location(offset);
+		let line_start = offset + 1 - column;
+
+		json!({ "line": line - 1, "character": self.source[line_start..offset].encode_utf16().count() })
+	}
+
+	fn range(&self, span: &ops::Range<usize>) -> Json {
+		json!({ "start": self.position(span.start), "end": self.position(span.end) })
+	}
+
+	/// Convert an LSP position into a byte offset
+	fn offset(&self, position: &Json) -> Option<usize> {
+		let line = self.index.line_span(position["line"].as_u64()? as usize + 1)?;
+		let character = position["character"].as_u64()? as usize;
+
+		let mut units = 0;
+		for (idx, c) in self.source[line.clone()].char_indices() {
+			if units >= character {
+				return Some(line.start + idx);
+			}
+			units += c.len_utf16();
+		}
+
+		Some(line.end)
+	}
+}
+
+/// Finds the name referenced by the innermost expression containing `offset`
+struct ReferenceAt {
+	offset: usize,
+	found: Option<(String, ops::Range<usize>)>,
+}
+
+impl Visitor for ReferenceAt {
+	fn visit_expression(&mut self, expr: &Expression) {
+		// Parameters are visited after their call, so the innermost expression is the last recorded
+		if expr.span().contains(&self.offset) {
+			self.found = referenced_name(expr);
+			walk_expression(self, expr);
+		}
+	}
+}
+
+/// The name referenced at `offset`, and the span of the name
+fn reference_at(exprs: &[Expression], offset: usize) -> Option<(String, ops::Range<usize>)> {
+	let mut visitor = ReferenceAt { offset, found: None };
+	visitor.visit_expressions(exprs);
+	visitor.found
+}
+
+/// If `expr` is a `define(name, value)`, the name, the span of the name and the defined value
+fn definition(expr: &Expression) -> Option<(String, ops::Range<usize>, Expression)> {
+	match expr {
+		Expression::FnCall {
+			function: Function::Host(name, _),
+			parameters,
+			..
+		} if name == "define" => match parameters.as_slice() {
+			[Expression::Word { name, span }, value] => Some((name.to_string(), span.clone(), value.clone())),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+/// Collects every `define(name, value)`, including those nested in other calls
+#[derive(Default)]
+struct Definitions(Vec<(String, ops::Range<usize>, Expression)>);
+
+impl Visitor for Definitions {
+	fn visit_expression(&mut self, expr: &Expression) {
+		self.0.extend(definition(expr));
+		walk_expression(self, expr);
+	}
+}
+
+/// Every `define(name, value)` in the script
+fn definitions(exprs: &[Expression]) -> Vec<(String, ops::Range<usize>, Expression)> {
+	let mut definitions = Definitions::default();
+	definitions.visit_expressions(exprs);
+	definitions.0
+}
+
+/// The parameter names of a `fn(...)` expression
+fn parameters(expr: &Expression) -> Option<Vec<String>> {
+	match expr {
+		Expression::FnCall {
+			function: Function::Host(name, _),
+			parameters,
+			..
+		} if name == "fn" => {
+			let names = parameters.iter().take(parameters.len().saturating_sub(1));
+			Some(names.filter_map(|p| if let Expression::Word { name, .. } = p { Some(name.to_string()) } else { None }).collect())
+		}
+		_ => None,
+	}
+}
+
+/// The name referenced by an expression, and the span of the name
+fn referenced_name(expr: &Expression) -> Option<(String, ops::Range<usize>)> {
+	match expr {
+		Expression::Word { name, span } => Some((name.to_string(), span.clone())),
+		Expression::FnCall { function, span, .. } => {
+			let name = match function {
+				Function::Host(name, _) | Function::Script(name) => name,
+			};
+			Some((name.to_string(), span.start..span.start + name.len()))
+		}
+		_ => None,
+	}
+}
+
+struct Server {
+	documents: BTreeMap<String, Document>,
+	operators: BTreeMap<&'static str, Box<dyn Operator>>,
+}
+
+impl Server {
+	fn parse(&self, uri: &str) -> Option<(&Document, Vec<Expression>)> {
+		let document = self.documents.get(uri)?;
+		let exprs = parser::parse(&document.source, &self.operators).ok()?;
+
+		Some((document, exprs))
+	}
+
+	fn publish_diagnostics(&self, uri: &str) {
+		let Some(document) = self.documents.get(uri) else { return };
+
+		let diagnostics = match parser::parse(&document.source, &self.operators) {
+			Ok(exprs) => Linter::default()
+				.lint(&exprs)
+				.into_iter()
+				.map(|warning| json!({ "range": document.range(&warning.span), "severity": SEVERITY_WARNING, "source": "egg", "code": warning.rule.name(), "message": warning.message }))
+				.collect(),
+			Err(err) => {
+				let span = err.span().unwrap_or(0..0);
+				vec![json!({ "range": document.range(&span), "severity": SEVERITY_ERROR, "source": "egg", "message": err.to_string() })]
+			}
+		};
+
+		send(json!({ "jsonrpc": "2.0", "method": "textDocument/publishDiagnostics", "params": { "uri": uri, "diagnostics": diagnostics } }));
+	}
+
+	fn hover(&self, params: &Json) -> Option<Json> {
+		let (document, exprs) = self.parse(params["textDocument"]["uri"].as_str()?)?;
+		let offset = document.offset(&params["position"])?;
+		let (name, span) = reference_at(&exprs, offset)?;
+
+		let contents = match self.operators.get(name.as_str()) {
+			Some(operator) => {
+				let documentation = operator.documentation()?;
+				let (signature, description) = documentation.split_once("\n\n").unwrap_or((documentation, ""));
+				format!("```egg\n{signature}\n```\n{description}")
+			}
+			// Top-level definitions in the script, with their doc comments
+			None => {
+				let definitions = docs::definitions(&document.source).ok()?;
+				let definition = definitions.into_iter().find(|definition| definition.name == name)?;
+
+				let documentation = definition.documentation.as_deref().unwrap_or_default();
+				format!("```egg\n{}\n```\n{documentation}", definition.signature())
+			}
+		};
+
+		Some(json!({ "contents": { "kind": "markdown", "value": contents }, "range": document.range(&span) }))
+	}
+
+	fn definition(&self, params: &Json) -> Option<Json> {
+		let uri = params["textDocument"]["uri"].as_str()?;
+		let (document, exprs) = self.parse(uri)?;
+		let offset = document.offset(&params["position"])?;
+		let (name, _) = reference_at(&exprs, offset)?;
+		let found = definitions(&exprs);
+
+		// Prefer the closest definition preceding the reference
+		let (_, span, _) = found
+			.iter()
+			.rev()
+			.find(|(defined, span, _)| *defined == name && span.start <= offset)
+			.or_else(|| found.iter().find(|(defined, ..)| *defined == name))?;
+		Some(json!({ "uri": uri, "range": document.range(span) }))
+	}
+
+	fn completion(&self, params: &Json) -> Option<Json> {
+		let mut items = self
+			.operators
+			.iter()
+			.map(|(name, operator)| {
+				let detail = operator.documentation().and_then(|docs| docs.lines().next()).unwrap_or_default();
+				json!({ "label": name, "kind": COMPLETION_FUNCTION, "detail": detail })
+			})
+			.collect::<Vec<_>>();
+
+		// Constants from the default scope
+		let constants = Scope::default();
+		items.extend(constants.bindings().keys().map(|name| json!({ "label": name.as_str(), "kind": COMPLETION_VARIABLE })));
+
+		if let Some((_, exprs)) = self.parse(params["textDocument"]["uri"].as_str()?) {
+			let found = definitions(&exprs);
+
+			let mut names = BTreeMap::new();
+			for (name, _, value) in &found {
+				let Some(parameters) = parameters(value) else {
+					names.insert(name.clone(), (COMPLETION_VARIABLE, String::new()));
+					continue;
+				};
+
+				names.insert(name.clone(), (COMPLETION_FUNCTION, format!("{name}({})", parameters.join(", "))));
+				for parameter in parameters {
+					names.entry(parameter).or_insert((COMPLETION_VARIABLE, String::new()));
+				}
+			}
+
+			items.extend(names.into_iter().map(|(label, (kind, detail))| json!({ "label": label, "kind": kind, "detail": detail })));
+		}
+
+		Some(json!(items))
+	}
+
+	fn document_symbols(&self, params: &Json) -> Option<Json> {
+		let (document, exprs) = self.parse(params["textDocument"]["uri"].as_str()?)?;
+
+		let symbols = exprs
+			.iter()
+			.filter_map(|expr| {
+				let (name, span, value) = definition(expr)?;
+				let kind = if parameters(&value).is_some() { SYMBOL_FUNCTION } else { SYMBOL_VARIABLE };
+
+				Some(json!({ "name": name, "kind": kind, "range": document.range(&expr.span()), "selectionRange": document.range(&span) }))
+			})
+			.collect::<Vec<_>>();
+
+		Some(json!(symbols))
+	}
+
+	fn handle(&mut self, message: &Json) {
+		let params = &message["params"];
+		let result = match message["method"].as_str().unwrap_or_default() {
+			"initialize" => Some(json!({
+				"capabilities": {
+					"textDocumentSync": 1,
+					"hoverProvider": true,
+					"definitionProvider": true,
+					"completionProvider": {},
+					"documentSymbolProvider": true,
+				},
+				"serverInfo": { "name": "egg-lsp", "version": env!("CARGO_PKG_VERSION") },
+			})),
+			"shutdown" => Some(Json::Null),
+			"exit" => process::exit(0),
+			"textDocument/didOpen" => {
+				let document = &params["textDocument"];
+				let uri = document["uri"].as_str().unwrap_or_default().to_string();
+
+				self.documents.insert(uri.clone(), Document::new(document["text"].as_str().unwrap_or_default().to_string()));
+				self.publish_diagnostics(&uri);
+				None
+			}
+			"textDocument/didChange" => {
+				let uri = params["textDocument"]["uri"].as_str().unwrap_or_default().to_string();
+
+				// Full document sync, the last change holds the entire text
+				if let Some(text) = params["contentChanges"].as_array().and_then(|changes| changes.last()).and_then(|change| change["text"].as_str()) {
+					self.documents.insert(uri.clone(), Document::new(text.to_string()));
+				}
+
+				self.publish_diagnostics(&uri);
+				None
+			}
+			"textDocument/didClose" => {
+				let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+				self.documents.remove(uri);
+
+				send(json!({ "jsonrpc": "2.0", "method": "textDocument/publishDiagnostics", "params": { "uri": uri, "diagnostics": [] } }));
+				None
+			}
+			"textDocument/hover" => Some(self.hover(params).unwrap_or(Json::Null)),
+			"textDocument/definition" => Some(self.definition(params).unwrap_or(Json::Null)),
+			"textDocument/completion" => Some(self.completion(params).unwrap_or(Json::Null)),
+			"textDocument/documentSymbol" => Some(self.document_symbols(params).unwrap_or(Json::Null)),
+			_ if message.get("id").is_some() => {
+				let error = json!({ "code": -32601, "message": "Method not found" });
+				return send(json!({ "jsonrpc": "2.0", "id": message["id"], "error": error }));
+			}
+			_ => None,
+		};
+
+		// Only requests carry an id and expect a response
+		if let (Some(id), Some(result)) = (message.get("id"), result) {
+			send(json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+		}
+	}
+}
+
+fn main() {
+	let mut server = Server {
+		documents: BTreeMap::new(),
+		operators: operators::empty(),
+	};
+	operators::full(&mut server.operators);
+
+	let mut stdin = io::stdin().lock();
+	while let Some(message) = receive(&mut stdin) {
+		server.handle(&message);
+	}
+}
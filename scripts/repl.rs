@@ -0,0 +1,199 @@
+use collections::BTreeMap;
+use egglang::{
+	debugger,
+	error::EggError,
+	evaluator,
+	expression::Value,
+	operators::Operator,
+	parser::{self, Token},
+	scope::Scope,
+};
+use logos::Logos;
+use std::*;
+
+static HELP: &str = "Enter Egg expressions to evaluate them, input continues over several lines until brackets are balanced.
+
+Commands:
+  :help           Show this message
+  :vars           Show variables defined in the session
+  :reset          Clear all variables
+  :load <path>    Evaluate a script in the session
+  :quit           Exit, as does Ctrl-D";
+
+/// A line read by the [`Prompt`]
+enum Line {
+	Text(String),
+	/// Ctrl-C, discarding the current input
+	#[cfg_attr(not(feature = "repl"), allow(dead_code))]
+	Interrupted,
+	/// Ctrl-D or the end of stdin
+	End,
+}
+
+/// Reads lines from the terminal, with line editing and history when the `repl` feature is enabled
+struct Prompt {
+	#[cfg(feature = "repl")]
+	editor: rustyline::DefaultEditor,
+}
+
+#[cfg(feature = "repl")]
+impl Prompt {
+	fn new() -> Prompt {
+		let mut editor = rustyline::DefaultEditor::new().unwrap();
+		if let Some(history) = history() {
+			let _ = editor.load_history(&history);
+		}
+
+		Prompt { editor }
+	}
+
+	fn read(&mut self, prompt: &str) -> Line {
+		match self.editor.readline(prompt) {
+			Ok(line) => Line::Text(line),
+			Err(rustyline::error::ReadlineError::Interrupted) => Line::Interrupted,
+			Err(_) => Line::End,
+		}
+	}
+
+	fn remember(&mut self, input: &str) {
+		let _ = self.editor.add_history_entry(input);
+	}
+}
+
+#[cfg(feature = "repl")]
+impl Drop for Prompt {
+	fn drop(&mut self) {
+		if let Some(history) = history() {
+			let _ = self.editor.save_history(&history);
+		}
+	}
+}
+
+#[cfg(feature = "repl")]
+fn history() -> Option<path::PathBuf> {
+	env::var_os("HOME").map(|home| path::Path::new(&home).join(".egg_history"))
+}
+
+#[cfg(not(feature = "repl"))]
+impl Prompt {
+	fn new() -> Prompt {
+		Prompt {}
+	}
+
+	fn read(&mut self, prompt: &str) -> Line {
+		use io::Write;
+
+		print!("{prompt}");
+		io::stdout().flush().unwrap();
+
+		let mut line = String::new();
+		match io::stdin().read_line(&mut line) {
+			Ok(0) | Err(_) => Line::End,
+			Ok(_) => Line::Text(line.trim_end_matches(['\r', '\n']).to_string()),
+		}
+	}
+
+	fn remember(&mut self, _input: &str) {}
+}
+
+/// How many more brackets are opened than closed in `input`, ignoring those in strings and comments
+fn depth(input: &str) -> isize {
+	Token::lexer(input)
+		.map(|token| match token {
+			Ok(Token::LeftBracket) => 1,
+			Ok(Token::RightBracket) => -1,
+			_ => 0,
+		})
+		.sum()
+}
+
+/// Parse and evaluate `input` in the session, printing the value of each expression besides Nil.
+/// Yields the exit code if the input called `exit(code)`.
+fn run(input: &str, scope: &mut Scope, operators: &BTreeMap<&'static str, Box<dyn Operator>>) -> Option<i32> {
+	let expressions = match parser::parse(input, operators) {
+		Ok(expressions) => expressions,
+		Err(err) => {
+			println!("Error: {err}");
+			return None;
+		}
+	};
+
+	for expression in &expressions {
+		match evaluator::evaluate(expression, scope) {
+			Ok(Value::Nil) => {}
+			Ok(value) => println!("{}", debugger::describe(&value, scope)),
+			Err(EggError::Exit(code)) => return Some(code),
+			Err(err) => {
+				println!("Error: {err}");
+				break;
+			}
+		}
+	}
+
+	None
+}
+
+/// Interactive session, evaluating expressions as they are entered. Yields the exit code if ended by `exit(code)`.
+pub fn repl(operators: &BTreeMap<&'static str, Box<dyn Operator>>) -> Option<i32> {
+	// Builtin constants are hidden from `:vars`
+	let constants = Scope::default().bindings().clone();
+	let mut scope = Scope::default();
+	let mut prompt = Prompt::new();
+
+	println!("Egg {}, type :help for a list of commands", env!("CARGO_PKG_VERSION"));
+
+	'session: loop {
+		let mut input = match prompt.read("egg> ") {
+			Line::Text(input) => input,
+			Line::Interrupted => continue,
+			Line::End => break,
+		};
+
+		while depth(&input) > 0 {
+			match prompt.read("...  ") {
+				Line::Text(line) => {
+					input.push('\n');
+					input.push_str(&line);
+				}
+				Line::Interrupted => continue 'session,
+				Line::End => break 'session,
+			}
+		}
+
+		let trimmed = input.trim();
+		if trimmed.is_empty() {
+			continue;
+		}
+		prompt.remember(trimmed);
+
+		let (command, argument) = trimmed.split_once(' ').unwrap_or((trimmed, ""));
+		match command {
+			":help" | ":h" => println!("{HELP}"),
+			":vars" | ":v" => {
+				for (name, value) in scope.bindings() {
+					if constants.get(name) != Some(value) {
+						println!("  {name} = {}", debugger::describe(value, &scope));
+					}
+				}
+			}
+			":reset" => scope = Scope::default(),
+			":load" | ":l" => match fs::read_to_string(argument.trim()) {
+				Ok(script) => {
+					if let Some(code) = run(&script, &mut scope, operators) {
+						return Some(code);
+					}
+				}
+				Err(err) => println!("Error: Cannot read {}: {err}", argument.trim()),
+			},
+			":quit" | ":q" => break,
+			command if command.starts_with(':') => println!("Unknown command: {command}, type :help for a list of commands"),
+			_ => {
+				if let Some(code) = run(&input, &mut scope, operators) {
+					return Some(code);
+				}
+			}
+		}
+	}
+
+	None
+}
@@ -1,24 +1,588 @@
+use cell::Cell;
 use collections::BTreeMap;
-use egglang::{evaluator, operators, parser};
+use egglang::{
+	debugger::{self, Debugger, Frontend, Paused, Step},
+	docs,
+	error::EggError,
+	evaluator,
+	expression::{Expression, Value},
+	formatter::{self, FormatOptions},
+	hooks::Hook,
+	lint::Linter,
+	operators::{self, Operator},
+	optimizer,
+	parser::{self, LineIndex},
+	precompiled,
+	scope::Scope,
+	testing::{Failure, TestRunner},
+};
+use io::{BufRead, IsTerminal, Read, Write};
+use ops::Range;
+use rc::Rc;
 use std::*;
 
+mod repl;
+
+/// Text front-end for the debugger, reading commands from stdin
+struct Console {
+	source: String,
+	operators: BTreeMap<&'static str, Box<dyn Operator>>,
+}
+
+static HELP: &str = "Commands:
+  c, continue     Run until the next breakpoint
+  s, step         Step into the next function call
+  n, next         Step over the current expression
+  o, out          Run until the current function returns
+  b <line>        Set a breakpoint
+  d <line>        Delete a breakpoint
+  l, locals       Show local variables
+  g, globals      Show global variables
+  bt, backtrace   Show active function calls
+  p <expr>        Evaluate an expression in the paused scope
+  q, quit         Stop the script";
+
+impl Frontend for Console {
+	fn paused(&mut self, paused: &mut Paused<'_>) -> Step {
+		let text = self.source.lines().nth(paused.line - 1).unwrap_or_default();
+		println!("[{:?}] {}:{} | {}", paused.reason, paused.line, paused.column, text.trim());
+
+		let stdin = io::stdin();
+		loop {
+			print!("(egg) ");
+			io::stdout().flush().unwrap();
+
+			let mut input = String::new();
+			if stdin.lock().read_line(&mut input).unwrap() == 0 {
+				return Step::Continue;
+			}
+
+			let (command, argument) = input.trim().split_once(' ').unwrap_or((input.trim(), ""));
+			match command {
+				"c" | "continue" => return Step::Continue,
+				"s" | "step" => return Step::StepIn,
+				"n" | "next" => return Step::StepOver,
+				"o" | "out" => return Step::StepOut,
+				"b" | "d" => match argument.trim().parse::<usize>() {
+					Ok(line) if command == "b" => drop(paused.breakpoints.insert(line)),
+					Ok(line) => drop(paused.breakpoints.remove(&line)),
+					Err(_) => println!("Expected a line number"),
+				},
+				"l" | "locals" => paused.locals().iter().for_each(|(name, value)| println!("  {name} = {}", debugger::describe(value, paused.scope))),
+				"g" | "globals" => paused.globals().iter().for_each(|(name, value)| println!("  {name} = {}", debugger::describe(value, paused.scope))),
+				"bt" | "backtrace" => paused.stack.iter().rev().for_each(|frame| {
+					let line = self.source[..frame.span.start].matches('\n').count() + 1;
+					println!("  {} @ line {line}", frame.name)
+				}),
+				"p" | "print" => match parser::parse(argument, &self.operators).and_then(|exprs| paused.evaluate(&exprs)) {
+					Ok(value) => println!("  {}", debugger::describe(&value, paused.scope)),
+					Err(err) => println!("  Error: {err}"),
+				},
+				"q" | "quit" => process::exit(0),
+				"h" | "help" => println!("{HELP}"),
+				"" => {}
+				_ => println!("Unknown command: {command}, type 'help' for a list of commands"),
+			}
+		}
+	}
+}
+
+static USAGE: &str = "Usage: egg [options] [command]
+
+Commands:
+  run <script> [args...]     Run a script, `-` reads it from stdin
+  repl                       Start an interactive session
+  fmt [--check] <script>     Format a script in place, or only check that it is formatted
+  lint <script>              Report common mistakes in a script
+  doc [--html] <script>      Print a Markdown or HTML reference page for a script
+  compile <script> <output>  Precompile a script, to be run without parsing
+  test [paths...]            Run tests in scripts, or in directories of scripts. Defaults to the current directory
+
+A script path without a command runs it. Without either, the script is read from stdin, or a REPL is started in a terminal.
+
+Options:
+  -e, --eval <expr>      Evaluate an expression and print it's value
+  --modules=<list>       Comma separated builtins to provide: core, objects, strings, system, console and process. Defaults to all
+  --no-std-lib           Only provide the core builtins
+  --fs=<dir>             Let the script read and write files in a directory, through the fs builtins
+  --fs-read-only         Only let the script read files in the --fs directory
+  --optimize             Optimize the script before running or compiling it
+  --dump-ast             Print the parsed script instead of running it
+  --stats                Print execution time and statistics after running
+  --profile <output>     Profile the script, writing collapsed stacks to output
+  --debug                Step through the script in the debugger
+  -h, --help             Show this message
+
+Exit codes:
+  0  Success
+  1  The script raised an error, or a check failed
+  2  Invalid usage
+  3  The script could not be parsed
+  4  A file could not be read or written";
+
+/// Process exit codes, see [`USAGE`]
+const FAILURE: i32 = 1;
+const USAGE_ERROR: i32 = 2;
+const PARSE_ERROR: i32 = 3;
+const IO_ERROR: i32 = 4;
+
+/// Reports a failure and yields the exit code
+type Outcome = Result<(), i32>;
+
+const COMMANDS: [&str; 7] = ["run", "repl", "fmt", "lint", "doc", "compile", "test"];
+const MODULES: [&str; 6] = ["core", "objects", "strings", "system", "console", "process"];
+
+#[derive(Default)]
+struct Options {
+	modules: Vec<String>,
+	eval: Option<String>,
+	optimize: bool,
+	dump_ast: bool,
+	stats: bool,
+	profile: Option<String>,
+	debug: bool,
+	check: bool,
+	html: bool,
+	/// Arguments following the script, passed to it by the `process` module
+	args: Vec<String>,
+	/// Sandbox directory of the `fs` module, which is only provided if set
+	fs_root: Option<String>,
+	fs_read_only: bool,
+}
+
+fn usage_error(message: impl fmt::Display) -> i32 {
+	eprintln!("error: {message}\n\n{USAGE}");
+	USAGE_ERROR
+}
+
+fn io_error(path: &str, err: io::Error) -> i32 {
+	eprintln!("error: Cannot access {path}: {err}");
+	IO_ERROR
+}
+
+/// Print an error, with the offending source line when it's location is known
+fn report(path: &str, source: &str, error: &EggError, span: Option<Range<usize>>) {
+	eprintln!("error: {error}");
+
+	let Some(span) = span.filter(|span| span.start <= source.len()) else {
+		return eprintln!("  --> {path}");
+	};
+
+	let index = LineIndex::new(source);
+	let (line, column) = index.location(span.start);
+	let text = index.line_span(line).map(|span| &source[span]).unwrap_or_default();
+
+	// Keep tabs, so the marker lines up with the text above it
+	let indent = text.chars().take(column - 1).map(|c| if c == '\t' { '\t' } else { ' ' }).collect::<String>();
+	let width = span.len().clamp(1, (text.len() + 1).saturating_sub(column).max(1));
+	let gutter = " ".repeat(line.to_string().len());
+
+	eprintln!("{gutter}--> {path}:{line}:{column}");
+	eprintln!("{gutter} |");
+	eprintln!("{line} | {text}");
+	eprintln!("{gutter} | {indent}{}", "^".repeat(width));
+}
+
+/// Records where the last runtime error was raised
+struct ErrorLocation(Rc<Cell<Option<Range<usize>>>>);
+
+impl Hook for ErrorLocation {
+	fn on_error(&mut self, _: &EggError, expr: &Expression, _: &mut Scope) {
+		self.0.set(Some(expr.span()));
+	}
+}
+
+fn builtins(options: &Options) -> Result<BTreeMap<&'static str, Box<dyn Operator>>, i32> {
+	let mut map = operators::empty();
+
+	for module in &options.modules {
+		match module.as_str() {
+			"core" => drop(operators::minimal(&mut map)),
+			"objects" => operators::objects(&mut map),
+			"strings" => operators::strings(&mut map),
+			"system" => operators::system(&mut map),
+			"console" => operators::console(&mut map),
+			"process" => operators::process(&mut map, options.args.iter().map(String::as_str)),
+			module => return Err(usage_error(format!("Unknown module {module}, expected one of: {}", MODULES.join(", ")))),
+		}
+	}
+
+	if let Some(root) = &options.fs_root {
+		operators::fs(
+			&mut map,
+			operators::Sandbox {
+				root: root.into(),
+				read: true,
+				write: !options.fs_read_only,
+			},
+		);
+	}
+
+	Ok(map)
+}
+
+/// Read a file, or stdin if the path is `-`
+fn read(path: &str) -> Result<Vec<u8>, i32> {
+	let mut bytes = Vec::new();
+	let result = match path {
+		"-" => io::stdin().read_to_end(&mut bytes).map(drop),
+		path => fs::read(path).map(|read| bytes = read),
+	};
+
+	result.map(|_| bytes).map_err(|err| io_error(path, err))
+}
+
+fn read_source(path: &str) -> Result<String, i32> {
+	String::from_utf8(read(path)?).map_err(|_| io_error(path, io::ErrorKind::InvalidData.into()))
+}
+
+/// Load a script's source, and it's expressions from the source or a precompiled script
+fn load(path: &str, options: &Options, operators: &BTreeMap<&'static str, Box<dyn Operator>>) -> Result<(String, Vec<Expression>), i32> {
+	let bytes = read(path)?;
+
+	let (source, expressions) = if precompiled::is_precompiled(&bytes) {
+		match precompiled::from_bytes(&bytes, operators) {
+			// The source of precompiled scripts isn't available
+			Ok(expressions) => (String::new(), expressions),
+			Err(err) => {
+				report(path, "", &err, None);
+				return Err(PARSE_ERROR);
+			}
+		}
+	} else {
+		let source = String::from_utf8(bytes).map_err(|_| io_error(path, io::ErrorKind::InvalidData.into()))?;
+
+		match parser::parse(&source, operators) {
+			Ok(expressions) => (source, expressions),
+			Err(err) => {
+				report(path, &source, &err, err.span());
+				return Err(PARSE_ERROR);
+			}
+		}
+	};
+
+	match options.optimize {
+		true => Ok((source, optimizer::optimize(expressions))),
+		false => Ok((source, expressions)),
+	}
+}
+
+/// Evaluate a script, returning the value of it's last expression
+fn execute(path: &str, source: &str, expressions: &[Expression], options: &Options) -> Result<Value, i32> {
+	let mut scope = Scope::default();
+	let location = Rc::new(Cell::new(None));
+	scope.add_hook(Box::new(ErrorLocation(location.clone())));
+	scope.set_operator_statistics(options.stats);
+
+	if options.profile.is_some() {
+		scope.start_profiling();
+	}
+
+	if options.debug {
+		let console = Console {
+			source: source.to_string(),
+			operators: builtins(options)?,
+		};
+
+		let mut debugger = Debugger::new(source, console);
+		debugger.stop_on_entry(true);
+		scope.add_hook(Box::new(debugger));
+	}
+
+	let then = time::Instant::now();
+	let mut result = Ok(Value::Nil);
+
+	for expression in expressions {
+		match evaluator::evaluate(expression, &mut scope) {
+			Ok(value) => result = Ok(value),
+			// Stopped by `exit(code)`
+			Err(EggError::Exit(code)) => {
+				result = if code == 0 { Ok(Value::Nil) } else { Err(code) };
+				break;
+			}
+			Err(err) => {
+				// Spans of precompiled scripts refer to a source we don't have
+				report(path, source, &err, location.take().filter(|_| !source.is_empty()));
+				return Err(FAILURE);
+			}
+		}
+	}
+
+	if options.stats {
+		let statistics = scope.statistics();
+		eprintln!("Execution took: {:?}", then.elapsed());
+		eprintln!("Evaluations: {}", statistics.evaluations);
+		eprintln!("Function calls: {} (peak depth {})", statistics.function_calls, statistics.peak_call_depth);
+		eprintln!("Objects allocated: {}", statistics.objects_allocated);
+		for (name, calls) in &statistics.operator_calls {
+			eprintln!("  {name}: {calls}");
+		}
+	}
+
+	if let (Some(output), Some(profile)) = (&options.profile, scope.stop_profiling()) {
+		eprintln!("\n{}", profile.report());
+		fs::write(output, profile.collapsed()).map_err(|err| io_error(output, err))?;
+	}
+
+	result
+}
+
+fn run(path: &str, options: &Options) -> Outcome {
+	let operators = builtins(options)?;
+	let (source, expressions) = load(path, options, &operators)?;
+
+	if options.dump_ast {
+		expressions.iter().for_each(|expr| println!("{expr:#?}"));
+		return Ok(());
+	}
+
+	execute(path, &source, &expressions, options).map(drop)
+}
+
+/// Evaluate an expression given on the command line, printing it's value
+fn eval(expression: &str, options: &Options) -> Outcome {
+	let operators = builtins(options)?;
+	let expressions = match parser::parse(expression, &operators) {
+		Ok(expressions) => expressions,
+		Err(err) => {
+			report("<eval>", expression, &err, err.span());
+			return Err(PARSE_ERROR);
+		}
+	};
+
+	if options.dump_ast {
+		expressions.iter().for_each(|expr| println!("{expr:#?}"));
+		return Ok(());
+	}
+
+	let mut scope = Scope::default();
+	let location = Rc::new(Cell::new(None));
+	scope.add_hook(Box::new(ErrorLocation(location.clone())));
+
+	let mut result = Value::Nil;
+	for expr in &expressions {
+		result = evaluator::evaluate(expr, &mut scope).map_err(|err| match err {
+			EggError::Exit(code) => code,
+			err => {
+				report("<eval>", expression, &err, location.take());
+				FAILURE
+			}
+		})?;
+	}
+
+	if result != Value::Nil {
+		println!("{}", debugger::describe(&result, &scope));
+	}
+	Ok(())
+}
+
+/// Format the script in place, or only report whether it is formatted when checking
+fn fmt(path: &str, options: &Options) -> Outcome {
+	let source = read_source(path)?;
+	let formatted = formatter::format(&source, &FormatOptions::default()).map_err(|err| {
+		report(path, &source, &err, err.span());
+		PARSE_ERROR
+	})?;
+
+	match (formatted == source, options.check) {
+		(true, _) => Ok(()),
+		(false, true) => {
+			eprintln!("{path} is not formatted");
+			Err(FAILURE)
+		}
+		// Formatted input from stdin is written to stdout
+		(false, false) if path == "-" => {
+			print!("{formatted}");
+			Ok(())
+		}
+		(false, false) => fs::write(path, formatted).map_err(|err| io_error(path, err)),
+	}
+}
+
+/// Report warnings from the linter, failing if there are any
+fn lint(path: &str, options: &Options) -> Outcome {
+	let operators = builtins(options)?;
+	let source = read_source(path)?;
+	let expressions = parser::parse(&source, &operators).map_err(|err| {
+		report(path, &source, &err, err.span());
+		PARSE_ERROR
+	})?;
+
+	let index = LineIndex::new(&source);
+	let warnings = Linter::default().lint(&expressions);
+	for warning in &warnings {
+		let (line, column) = index.location(warning.span.start);
+		println!("{path}:{line}:{column}: warning: {warning}");
+	}
+
+	if warnings.is_empty() { Ok(()) } else { Err(FAILURE) }
+}
+
+/// Print a reference page for the definitions in the script
+fn doc(path: &str, options: &Options) -> Outcome {
+	let source = read_source(path)?;
+	let definitions = docs::definitions(&source).map_err(|err| {
+		report(path, &source, &err, err.span());
+		PARSE_ERROR
+	})?;
+	let title = path::Path::new(path).file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+
+	match options.html {
+		true => print!("{}", docs::html(&title, &definitions)),
+		false => print!("{}", docs::markdown(&title, &definitions)),
+	}
+	Ok(())
+}
+
+fn compile(path: &str, output: &str, options: &Options) -> Outcome {
+	let operators = builtins(options)?;
+	let (_, expressions) = load(path, options, &operators)?;
+
+	let bytes = precompiled::to_bytes(&expressions).map_err(|err| {
+		report(path, "", &err, None);
+		FAILURE
+	})?;
+	fs::write(output, bytes).map_err(|err| io_error(output, err))
+}
+
+/// Scripts under `path` with tests or expected output, sorted by path
+fn discover(path: &path::Path, scripts: &mut Vec<path::PathBuf>) -> io::Result<()> {
+	if path.is_file() {
+		scripts.push(path.to_path_buf());
+		return Ok(());
+	}
+
+	let mut entries = fs::read_dir(path)?.map(|entry| entry.map(|entry| entry.path())).collect::<io::Result<Vec<_>>>()?;
+	entries.sort();
+
+	for entry in entries {
+		if entry.is_dir() {
+			discover(&entry, scripts)?;
+		} else if entry.extension().is_some_and(|ext| ext == "egg") {
+			scripts.push(entry);
+		}
+	}
+
+	Ok(())
+}
+
+/// Run the tests in each script, comparing their output to `<script>.expected` if present
+fn test(paths: &[&str], options: &Options) -> Outcome {
+	let runner = TestRunner::new(builtins(options)?);
+	let mut scripts = Vec::new();
+	for path in paths {
+		discover(path::Path::new(path), &mut scripts).map_err(|err| io_error(path, err))?;
+	}
+
+	let (mut passed, mut failures) = (0, Vec::new());
+	for script in &scripts {
+		let path = script.display().to_string();
+		let source = read_source(&path)?;
+		let expected = match fs::read_to_string(script.with_extension("expected")) {
+			Ok(expected) => Some(expected),
+			Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+			Err(err) => return Err(io_error(&path, err)),
+		};
+
+		let results = runner.run(&source, expected.as_deref()).map_err(|err| {
+			report(&path, &source, &err, err.span());
+			PARSE_ERROR
+		})?;
+
+		for result in results {
+			match result.passed() {
+				true => passed += 1,
+				false => failures.push((path.clone(), source.clone(), result)),
+			}
+		}
+	}
+
+	for (path, source, result) in &failures {
+		let (line, _) = LineIndex::new(source).location(result.span.start);
+		println!("FAILED {path}:{line} {}: {}", result.name, result.failure.as_ref().map(ToString::to_string).unwrap_or_default());
+
+		if let Some(Failure::Output { expected }) = &result.failure {
+			println!("--- expected\n{expected}\n--- printed\n{}", result.output);
+		}
+	}
+
+	println!("{passed} passed, {} failed, in {} scripts", failures.len(), scripts.len());
+	if failures.is_empty() { Ok(()) } else { Err(FAILURE) }
+}
+
+fn interactive(options: &Options) -> Outcome {
+	match repl::repl(&builtins(options)?) {
+		Some(code) if code != 0 => Err(code),
+		_ => Ok(()),
+	}
+}
+
 fn main() {
-	match env::args().nth(1) {
-		Some(path) => {
-			let mut scope = Default::default();
-			let mut operators = BTreeMap::new();
-			operators::full(&mut operators);
+	if let Err(code) = cli(env::args().skip(1)) {
+		process::exit(code);
+	}
+}
 
-			let file = fs::read_to_string(path).unwrap();
-			let expressions = parser::parse(&file, &operators).unwrap();
-			let then = std::time::Instant::now();
+fn cli(mut args: impl Iterator<Item = String>) -> Outcome {
+	let mut options = Options {
+		modules: MODULES.map(String::from).to_vec(),
+		..Options::default()
+	};
+	// The command and it's operands
+	let mut positional = Vec::new();
 
-			for expression in expressions {
-				evaluator::evaluate(&expression, &mut scope).unwrap();
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"-h" | "--help" => {
+				println!("{USAGE}");
+				return Ok(());
 			}
+			"-e" | "--eval" => options.eval = Some(args.next().ok_or_else(|| usage_error("--eval expects an expression"))?),
+			"--no-std-lib" => options.modules = vec!["core".to_string()],
+			"--optimize" => options.optimize = true,
+			"--dump-ast" => options.dump_ast = true,
+			"--stats" => options.stats = true,
+			"--profile" => options.profile = Some(args.next().ok_or_else(|| usage_error("--profile expects an output path"))?),
+			"--debug" => options.debug = true,
+			"--check" => options.check = true,
+			"--html" => options.html = true,
+			"--fs-read-only" => options.fs_read_only = true,
+			"--" => positional.extend(args.by_ref()),
+			arg if arg.starts_with("--modules=") => options.modules = arg["--modules=".len()..].split(',').map(String::from).collect(),
+			arg if arg.starts_with("--fs=") => options.fs_root = Some(arg["--fs=".len()..].to_string()),
+			arg if arg.starts_with('-') && arg != "-" => return Err(usage_error(format!("Unknown option {arg}"))),
+			_ => positional.push(arg),
+		}
 
-			println!("Execution Took: {:?}", then.elapsed());
+		// Everything after the script to run belongs to it
+		let runs = match positional.first().map(String::as_str) {
+			Some("run") => positional.len() == 2,
+			Some(command) => !COMMANDS.contains(&command),
+			None => false,
+		};
+		if runs {
+			options.args.extend(args.by_ref());
 		}
-		None => eprintln!("No script path provided"),
+	}
+
+	if let Some(expression) = &options.eval {
+		return eval(expression, &options);
+	}
+
+	let positional = positional.iter().map(String::as_str).collect::<Vec<_>>();
+	match positional.as_slice() {
+		["repl"] => interactive(&options),
+		["fmt", path] => fmt(path, &options),
+		["lint", path] => lint(path, &options),
+		["doc", path] => doc(path, &options),
+		["compile", path, output] => compile(path, output, &options),
+		["test"] => test(&["."], &options),
+		["test", paths @ ..] => test(paths, &options),
+		["run", path] => run(path, &options),
+		[command, ..] if COMMANDS.contains(command) => Err(usage_error(format!("Invalid arguments for {command}"))),
+		[path] => run(path, &options),
+		[] if io::stdin().is_terminal() => interactive(&options),
+		[] => run("-", &options),
+		_ => Err(usage_error("Invalid command")),
 	}
 }
@@ -0,0 +1,266 @@
+use alloc::{collections::BTreeSet, string::String, vec, vec::Vec};
+use arcstr::ArcStr;
+use core::ops::Range;
+
+use crate::{
+	error::EggResult,
+	evaluator::evaluate,
+	expression::{Expression, Value},
+	hooks::Hook,
+	parser::LineIndex,
+	scope::Scope,
+};
+
+/// How execution should proceed after the debugger pauses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+	/// Run until the next breakpoint
+	Continue,
+	/// Pause at the next function call, including calls within the current expression and inside called functions
+	StepIn,
+	/// Pause at the next function call after the current expression has been evaluated
+	StepOver,
+	/// Pause once the current Egg function has returned
+	StepOut,
+}
+
+/// Why the debugger paused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseReason {
+	/// The first expression of the script is about to run, see [`Debugger::stop_on_entry`]
+	Entry,
+	/// A breakpoint was hit
+	Breakpoint,
+	/// A [`Step`] completed
+	Step,
+	/// The [`Frontend`] requested a pause while running
+	Request,
+}
+
+/// An active function call, the innermost frame is last
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+	/// The name the function was called with, `<main>` for the script itself
+	pub name: ArcStr,
+	/// Span of the expression currently executing in this frame
+	pub span: Range<usize>,
+}
+
+/// State exposed to a [`Frontend`] while execution is paused
+pub struct Paused<'a> {
+	/// Why execution paused
+	pub reason: PauseReason,
+	/// The expression about to be evaluated
+	pub expression: &'a Expression,
+	/// 1-based line of the expression
+	pub line: usize,
+	/// 1-based column of the expression
+	pub column: usize,
+	/// Active function calls, the innermost frame is last
+	pub stack: &'a [Frame],
+	/// Line numbers with breakpoints, may be modified while paused
+	pub breakpoints: &'a mut BTreeSet<usize>,
+	/// The scope the paused expression is evaluated in
+	pub scope: &'a mut Scope,
+}
+
+impl Paused<'_> {
+	/// Variables local to the paused function. Empty if paused in the global scope.
+	pub fn locals(&self) -> Vec<(ArcStr, Value)> {
+		match self.scope {
+			Scope::Global { .. } => Vec::new(),
+			Scope::Local { .. } => self.scope.bindings().iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+		}
+	}
+
+	/// Variables in the global scope
+	pub fn globals(&self) -> Vec<(ArcStr, Value)> {
+		self.scope.global_bindings().iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+	}
+
+	/// Evaluate `expressions` in the paused scope, yielding the value of the last. Breakpoints are not triggered while evaluating.
+	pub fn evaluate(&mut self, expressions: &[Expression]) -> EggResult<Value> {
+		expressions.iter().try_fold(Value::Nil, |_, expr| evaluate(expr, self.scope))
+	}
+}
+
+/// Presents paused execution to the user, and decides how to proceed.
+pub trait Frontend {
+	/// Called whenever execution pauses. Blocks evaluation until it returns.
+	fn paused(&mut self, paused: &mut Paused<'_>) -> Step;
+
+	/// Called before each function call while running, allowing the frontend to poll for input.
+	/// Returning `true` pauses execution.
+	#[allow(unused_variables)]
+	fn running(&mut self, breakpoints: &mut BTreeSet<usize>) -> bool {
+		false
+	}
+}
+
+/// A step debugger, installed on a [`Scope`] as a [`Hook`].
+///
+/// Execution pauses before function calls on lines with breakpoints, or after a [`Step`] completes.
+/// The [`Frontend`] is then invoked synchronously, with access to the paused [`Scope`].
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::debugger::{Debugger, Frontend, Paused, Step};
+///
+/// // Logs the line and value of `x` at every pause
+/// struct Log(std::rc::Rc<std::cell::RefCell<Vec<(usize, Value)>>>);
+///
+/// impl Frontend for Log {
+///     fn paused(&mut self, paused: &mut Paused<'_>) -> Step {
+///         let x = paused.scope.get("x").cloned().unwrap_or(Value::Nil);
+///         self.0.borrow_mut().push((paused.line, x));
+///         Step::Continue
+///     }
+/// }
+///
+/// let script = "define(x, 1)\nset(x, 2)\nset(x, 3)";
+/// let log = Default::default();
+///
+/// let mut operators = operators::empty();
+/// operators::minimal(&mut operators);
+///
+/// let mut debugger = Debugger::new(script, Log(std::rc::Rc::clone(&log)));
+/// debugger.add_breakpoint(3);
+///
+/// let mut scope = Scope::default();
+/// scope.add_hook(Box::new(debugger));
+///
+/// for expr in parse(script, &operators).unwrap() {
+///     evaluate(&expr, &mut scope).unwrap();
+/// }
+///
+/// assert_eq!(*log.borrow(), [(3, 2.0.into())]);
+/// ```
+pub struct Debugger<F> {
+	frontend: F,
+	index: LineIndex,
+	breakpoints: BTreeSet<usize>,
+	step: Option<(Step, usize, usize)>,
+	stop_on_entry: bool,
+	frames: Vec<Frame>,
+	/// Lines of the expressions currently being evaluated, the innermost is last
+	entered: Vec<usize>,
+}
+
+impl<F: Frontend> Debugger<F> {
+	/// Create a debugger for `source`, the script about to be evaluated
+	pub fn new(source: &str, frontend: F) -> Debugger<F> {
+		Debugger {
+			frontend,
+			index: LineIndex::new(source),
+			breakpoints: BTreeSet::new(),
+			step: None,
+			stop_on_entry: false,
+			frames: vec![Frame {
+				name: arcstr::literal!("<main>"),
+				span: 0..0,
+			}],
+			entered: Vec::new(),
+		}
+	}
+
+	/// Pause before the first function call
+	pub fn stop_on_entry(&mut self, stop: bool) {
+		self.stop_on_entry = stop;
+	}
+
+	/// Pause whenever a function call starting on the 1-based `line` is evaluated
+	pub fn add_breakpoint(&mut self, line: usize) {
+		self.breakpoints.insert(line);
+	}
+
+	/// Remove a breakpoint, returning whether it existed
+	pub fn remove_breakpoint(&mut self, line: usize) -> bool {
+		self.breakpoints.remove(&line)
+	}
+
+	fn should_pause(&mut self, line: usize) -> Option<PauseReason> {
+		let depth = self.frames.len();
+		let nesting = self.entered.len();
+
+		if core::mem::take(&mut self.stop_on_entry) {
+			return Some(PauseReason::Entry);
+		}
+
+		if self.frontend.running(&mut self.breakpoints) {
+			return Some(PauseReason::Request);
+		}
+
+		let stepped = match self.step {
+			Some((Step::StepIn, ..)) => true,
+			Some((Step::StepOver, step_depth, step_nesting)) => depth < step_depth || (depth == step_depth && nesting <= step_nesting),
+			Some((Step::StepOut, step_depth, _)) => depth < step_depth,
+			Some((Step::Continue, ..)) | None => false,
+		};
+
+		if stepped {
+			return Some(PauseReason::Step);
+		}
+
+		// Only the outermost call on a line triggers it's breakpoint
+		let parent_line = self.entered.last().copied();
+		(self.breakpoints.contains(&line) && parent_line != Some(line)).then_some(PauseReason::Breakpoint)
+	}
+}
+
+impl<F: Frontend> Hook for Debugger<F> {
+	fn on_enter(&mut self, expr: &Expression, scope: &mut Scope) {
+		let span = expr.span();
+		let (line, column) = self.index.location(span.start);
+
+		if let Expression::FnCall { .. } = expr {
+			if let Some(frame) = self.frames.last_mut() {
+				frame.span = span;
+			}
+
+			if let Some(reason) = self.should_pause(line) {
+				let mut paused = Paused {
+					reason,
+					expression: expr,
+					line,
+					column,
+					stack: &self.frames,
+					breakpoints: &mut self.breakpoints,
+					scope,
+				};
+
+				let step = self.frontend.paused(&mut paused);
+				self.step = Some((step, self.frames.len(), self.entered.len()));
+			}
+		}
+
+		self.entered.push(line);
+	}
+
+	fn on_exit(&mut self, _: &Expression, _: &EggResult<Value>, _: &mut Scope) {
+		self.entered.pop();
+	}
+
+	fn on_call(&mut self, name: &str, _: &[Value], _: &mut Scope) {
+		self.frames.push(Frame { name: name.into(), span: 0..0 });
+	}
+
+	fn on_return(&mut self, _: &str, _: &EggResult<Value>, _: &mut Scope) {
+		if self.frames.len() > 1 {
+			self.frames.pop();
+		}
+	}
+}
+
+/// Render a [`Value`] for display in a debugger, resolving objects and functions through the `scope`
+pub fn describe(value: &Value, scope: &Scope) -> String {
+	use alloc::format;
+
+	match value {
+		Value::Function(idx) => scope.get_function_definition(*idx).map(|f| format!("{f:?}")).unwrap_or_else(|_| format!("{value:?}")),
+		Value::Object(tag) => match scope.get_object_tag(value.clone()) {
+			Ok(_) => format!("{:?}", scope.get_object(*tag)),
+			Err(_) => format!("{value:?}"),
+		},
+		value => format!("{value}"),
+	}
+}
@@ -0,0 +1,141 @@
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+use core::{fmt::Write, ops::Range};
+
+use crate::{
+	error::EggResult,
+	parser::Token,
+	syntax::{self, SyntaxNode},
+};
+
+/// A top-level definition in a script: `define(name, value)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+	pub name: String,
+	/// Parameter names, if the value is a function: `fn(parameters..., body)`
+	pub parameters: Option<Vec<String>>,
+	/// Text of the `##` doc comments directly above the definition, see [`SyntaxNode::doc_comment`]
+	pub documentation: Option<String>,
+	/// Span of the `define(...)` expression
+	pub span: Range<usize>,
+}
+
+impl Definition {
+	/// The definition as it's called, eg: `plusOne(n)` for functions, or just the name for variables
+	pub fn signature(&self) -> String {
+		match &self.parameters {
+			Some(parameters) => format!("{}({})", self.name, parameters.join(", ")),
+			None => self.name.clone(),
+		}
+	}
+}
+
+/// Collects the top-level definitions of a script, along with their documentation.
+///
+/// ```
+/// use egglang::docs;
+///
+/// let script = "## Adds one to `n`\ndefine(plusOne, fn(n, sum(n, 1)))\ndefine(PI, 3.14159)";
+/// let definitions = docs::definitions(script).unwrap();
+///
+/// assert_eq!(definitions[0].signature(), "plusOne(n)");
+/// assert_eq!(definitions[0].documentation.as_deref(), Some("Adds one to `n`"));
+/// assert_eq!(definitions[1].signature(), "PI");
+/// ```
+pub fn definitions(source: &str) -> EggResult<Vec<Definition>> {
+	let tree = syntax::parse(source)?;
+	Ok(tree.nodes.iter().filter_map(definition).collect())
+}
+
+fn definition(node: &SyntaxNode) -> Option<Definition> {
+	let SyntaxNode::Call { name, arguments, .. } = node else { return None };
+	let [SyntaxNode::Atom(variable), value] = arguments.as_slice() else { return None };
+
+	if name.text != "define" || variable.kind != Token::Word {
+		return None;
+	}
+
+	let parameters = match value {
+		SyntaxNode::Call { name, arguments, .. } if name.text == "fn" => {
+			let parameters = arguments.iter().take(arguments.len().saturating_sub(1));
+			Some(parameters.filter_map(|p| if let SyntaxNode::Atom(token) = p { Some(token.text.to_string()) } else { None }).collect())
+		}
+		_ => None,
+	};
+
+	Some(Definition {
+		name: variable.text.to_string(),
+		parameters,
+		documentation: node.doc_comment(),
+		span: node.span(),
+	})
+}
+
+/// Renders a Markdown reference page for a library of `definitions`, functions are listed before variables.
+/// Documentation is included as written, so it may itself use Markdown.
+pub fn markdown(title: &str, definitions: &[Definition]) -> String {
+	let mut out = format!("# {title}\n");
+
+	for (heading, functions) in [("Functions", true), ("Variables", false)] {
+		let mut section = definitions.iter().filter(|d| d.parameters.is_some() == functions).peekable();
+		if section.peek().is_none() {
+			continue;
+		}
+
+		let _ = write!(out, "\n## {heading}\n");
+		for definition in section {
+			let _ = write!(out, "\n### `{}`\n", definition.signature());
+			if let Some(documentation) = &definition.documentation {
+				let _ = write!(out, "\n{documentation}\n");
+			}
+		}
+	}
+
+	out
+}
+
+/// Renders a standalone HTML reference page for a library of `definitions`, functions are listed before variables.
+/// Paragraphs in the documentation are separated by blank lines.
+pub fn html(title: &str, definitions: &[Definition]) -> String {
+	let mut out = format!(
+		"<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n",
+		escape(title)
+	);
+
+	for (heading, functions) in [("Functions", true), ("Variables", false)] {
+		let mut section = definitions.iter().filter(|d| d.parameters.is_some() == functions).peekable();
+		if section.peek().is_none() {
+			continue;
+		}
+
+		let _ = writeln!(out, "<h2>{heading}</h2>");
+		for definition in section {
+			let _ = writeln!(out, "<section id=\"{}\">\n<h3><code>{}</code></h3>", escape(&definition.name), escape(&definition.signature()));
+
+			for paragraph in definition.documentation.iter().flat_map(|docs| docs.split("\n\n")) {
+				let _ = writeln!(out, "<p>{}</p>", escape(paragraph.trim()));
+			}
+			out.push_str("</section>\n");
+		}
+	}
+
+	out.push_str("</body>\n</html>\n");
+	out
+}
+
+fn escape(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+	for c in text.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			c => escaped.push(c),
+		}
+	}
+	escaped
+}
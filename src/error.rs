@@ -28,8 +28,88 @@ pub enum EggError {
 	AssertionFailed(Value),
 	#[error("Generic parsing error at: {0:?}. Reason: {1}")]
 	ParserError(Range<usize>, String),
-	#[error("Unknown Token found in TokenStream: {0}")]
-	UnknownToken(String),
+	#[error("Unknown Token found in TokenStream: {0}, at: {1:?}")]
+	UnknownToken(String, Range<usize>),
 	#[error("{0}")]
 	InvalidFunctionCall(String),
+	#[error("Memory limit exceeded: {0}")]
+	MemoryLimitExceeded(String),
+	#[error("Evaluation was cancelled by the host")]
+	Cancelled,
+	#[error("Evaluation exceeded it's deadline")]
+	Timeout,
+	#[error("Invalid precompiled script: {0}")]
+	InvalidPrecompiledScript(String),
+	/// Raised by `exit(code)` to stop the script, hosts decide whether to exit the process
+	#[error("Script exited with code {0}")]
+	Exit(i32),
+	/// Raised by `panic(value)`, with the location of the call. See [`Hook::on_panic`](crate::hooks::Hook::on_panic) to make panics fatal
+	#[error("Script panicked: {value}")]
+	ScriptPanic { value: Value, location: Option<Range<usize>> },
+	/// Raised by `throw(value)`, and caught by `try`
+	#[error("Uncaught error thrown: {0}")]
+	Thrown(Value),
+	/// Raised by the `fs` builtins, with the path as given by the script
+	#[error("Cannot access file {path}: {reason}")]
+	FileSystem { path: String, reason: FileSystemError },
+}
+
+/// Why a file operation of the `fs` builtins failed, see [`Sandbox`](crate::operators::Sandbox)
+#[derive(Clone, Debug, PartialEq, Eq, thiserror_no_std::Error)]
+pub enum FileSystemError {
+	#[error("the path is outside of the sandbox")]
+	OutsideSandbox,
+	#[error("reading files is not permitted")]
+	ReadDenied,
+	#[error("writing files is not permitted")]
+	WriteDenied,
+	#[error("no such file or directory")]
+	NotFound,
+	#[error("{0}")]
+	Io(String),
+}
+
+impl EggError {
+	/// Name of the variant, exposed to scripts as the `kind` of errors caught by `try`
+	pub fn kind(&self) -> &'static str {
+		match self {
+			EggError::UndefinedBinding(_) => "UndefinedBinding",
+			EggError::FunctionNotFound(_) => "FunctionNotFound",
+			EggError::UnbalancedBrackets(_) => "UnbalancedBrackets",
+			EggError::OperatorComplaint(_) => "OperatorComplaint",
+			EggError::InvalidObjectKey(_) => "InvalidObjectKey",
+			EggError::InvalidObjectReference(_) => "InvalidObjectReference",
+			EggError::InvalidFunctionDefinition(_) => "InvalidFunctionDefinition",
+			EggError::AssertionFailed(_) => "AssertionFailed",
+			EggError::ParserError(..) => "ParserError",
+			EggError::UnknownToken(..) => "UnknownToken",
+			EggError::InvalidFunctionCall(_) => "InvalidFunctionCall",
+			EggError::MemoryLimitExceeded(_) => "MemoryLimitExceeded",
+			EggError::Cancelled => "Cancelled",
+			EggError::Timeout => "Timeout",
+			EggError::InvalidPrecompiledScript(_) => "InvalidPrecompiledScript",
+			EggError::Exit(_) => "Exit",
+			EggError::ScriptPanic { .. } => "ScriptPanic",
+			EggError::Thrown(_) => "Thrown",
+			EggError::FileSystem { .. } => "FileSystem",
+		}
+	}
+
+	/// Whether scripts may recover from this error using `try`.
+	/// Exiting, cancellation, timeouts and exceeded memory limits are enforced by the host, and panics are unrecoverable by definition, so always stop the script.
+	pub fn is_catchable(&self) -> bool {
+		!matches!(
+			self,
+			EggError::Exit(_) | EggError::Cancelled | EggError::Timeout | EggError::MemoryLimitExceeded(_) | EggError::ScriptPanic { .. }
+		)
+	}
+
+	/// The location in the source script of errors raised while parsing
+	pub fn span(&self) -> Option<Range<usize>> {
+		match self {
+			EggError::UnbalancedBrackets(offset) => Some(*offset..*offset + 1),
+			EggError::ParserError(span, _) | EggError::UnknownToken(_, span) => Some(span.clone()),
+			_ => None,
+		}
+	}
 }
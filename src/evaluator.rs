@@ -4,11 +4,9 @@ use crate::{
 	scope::Scope,
 };
 
-/// Increments for each call to [`evaluate`], including internal calls
-pub static mut EVALUATIONS: u128 = 0;
-
 /// Given an [`Expression`], evaluate and yield a [`Value`].
 /// Requires that the user assemble a [`Scope`] and a map of [`Operator`]s.
+/// Each call is counted in the scope's [`Statistics`](crate::scope::Statistics), and reported to any installed [`Hooks`](crate::hooks::Hook).
 ///
 /// ```
 /// use egglang::prelude::*;
@@ -28,19 +26,43 @@ pub static mut EVALUATIONS: u128 = 0;
 /// assert_eq!(result, 11.0.into());
 /// ```
 pub fn evaluate(expr: &Expression, scope: &mut Scope) -> EggResult<Value> {
-	unsafe { EVALUATIONS += 1 };
+	scope.tick()?;
+	scope.hook_enter(expr);
+
+	let mut result = dispatch(expr, scope);
+
+	// Panics are located at the `panic(...)` call raising them
+	if let Err(EggError::ScriptPanic { value, location: location @ None }) = &mut result {
+		*location = Some(expr.span());
+		scope.hook_panic(value, expr.span());
+	}
+
+	scope.hook_exit(expr, &result);
+	result
+}
 
+fn dispatch(expr: &Expression, scope: &mut Scope) -> EggResult<Value> {
 	match expr {
-		Expression::Value { value } => Ok(value.clone()),
-		Expression::Word { name } => scope.get(name.as_str()).ok_or_else(|| EggError::UndefinedBinding(name.clone())).cloned(),
-		Expression::FnCall { function: identifier, parameters } => match identifier {
+		Expression::Value { value, .. } => Ok(value.clone()),
+		Expression::Word { name, .. } => scope.get(name.as_str()).ok_or_else(|| EggError::UndefinedBinding(name.clone())).cloned(),
+		Expression::FnCall { function: identifier, parameters, .. } => match identifier {
 			Function::Script(name) => {
 				let idx = scope.get_function(name).ok_or_else(|| EggError::FunctionNotFound(name.clone()))?;
-				scope.call_function(idx, parameters)
+				scope.call_named_function(name, idx, parameters)
 			}
-			Function::Host(op) => {
+			Function::Host(name, op) => {
+				scope.record_operator_call(name);
 				let op = unsafe { op.as_ref().unwrap_unchecked() };
-				op.evaluate(parameters, scope)
+
+				#[cfg(feature = "std")]
+				scope.profile_enter_operator(name);
+
+				let result = op.evaluate(parameters, scope);
+
+				#[cfg(feature = "std")]
+				scope.profile_exit();
+
+				result
 			}
 		},
 	}
@@ -1,24 +1,220 @@
 use alloc::vec::Vec;
 use arcstr::ArcStr;
+use core::{fmt, ops::Range};
 use ordered_float::OrderedFloat;
 
 use crate::operators::Operator;
 
 /// An expression is a piece of code that can be evaluated into a [`Value`].
+///
+/// Every expression records the byte range it was parsed from as it's `span`.
 #[derive(Debug, Clone)]
 pub enum Expression {
-	Value { value: Value },
-	Word { name: ArcStr },
-	FnCall { function: Function, parameters: Vec<Expression> },
+	Value { value: Value, span: Range<usize> },
+	Word { name: ArcStr, span: Range<usize> },
+	FnCall { function: Function, parameters: Vec<Expression>, span: Range<usize> },
+}
+
+impl Expression {
+	/// The byte range in the source script this expression was parsed from
+	pub fn span(&self) -> Range<usize> {
+		match self {
+			Expression::Value { span, .. } | Expression::Word { span, .. } | Expression::FnCall { span, .. } => span.clone(),
+		}
+	}
+}
+
+/// Renders the expression as Egg source, which parses back into an equivalent expression.
+///
+/// Arguments are separated by `", "` on a single line, see [`format`](crate::formatter::format) for a canonical layout.
+/// Values without a literal are rendered as expressions producing them: `Nil` as `do()`, and infinities or NaN as a `divide(...)`.
+/// Strings containing `"` or line breaks, and references to functions or objects, have no representation in Egg source.
+///
+/// ```
+/// use egglang::prelude::*;
+///
+/// let mut operators = operators::empty();
+/// operators::minimal(&mut operators);
+///
+/// let script = parse("define(x,  sum(1 2.5)) if(True \"yes\" , \"no\")", &operators).unwrap();
+/// let source = script.iter().map(|expr| expr.to_string()).collect::<Vec<_>>();
+///
+/// assert_eq!(source, ["define(x, sum(1, 2.5))", "if(True, \"yes\", \"no\")"]);
+/// ```
+impl fmt::Display for Expression {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Expression::Value { value, .. } => match value {
+				Value::Nil => f.write_str("do()"),
+				Value::Number(n) if n.is_nan() => f.write_str("divide(0, 0)"),
+				Value::Number(n) if n.is_infinite() => write!(f, "divide({}, 0)", n.signum()),
+				value => write!(f, "{value}"),
+			},
+			Expression::Word { name, .. } => f.write_str(name),
+			Expression::FnCall { function, parameters, .. } => {
+				let name = match function {
+					Function::Host(name, _) | Function::Script(name) => name,
+				};
+
+				write!(f, "{name}(")?;
+				for (idx, parameter) in parameters.iter().enumerate() {
+					if idx > 0 {
+						f.write_str(", ")?;
+					}
+					write!(f, "{parameter}")?;
+				}
+				f.write_str(")")
+			}
+		}
+	}
+}
+
+/// Read-only traversal of [`Expressions`](Expression).
+///
+/// Every method defaults to walking into the children of the node, override the ones of interest and call the matching `walk_*` function to keep descending.
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::expression::{Function, Visitor, walk_call};
+///
+/// // Collect the names of every function called in a script
+/// #[derive(Default)]
+/// struct Calls(Vec<String>);
+///
+/// impl Visitor for Calls {
+///     fn visit_call(&mut self, function: &Function, parameters: &[Expression], span: &std::ops::Range<usize>) {
+///         if let Function::Host(name, _) | Function::Script(name) = function {
+///             self.0.push(name.to_string());
+///         }
+///         walk_call(self, function, parameters, span);
+///     }
+/// }
+///
+/// let mut operators = operators::empty();
+/// operators::minimal(&mut operators);
+///
+/// let script = parse("define(x, sum(1, multiply(2, 3))) greet(x)", &operators).unwrap();
+/// let mut calls = Calls::default();
+/// calls.visit_expressions(&script);
+///
+/// assert_eq!(calls.0, ["define", "sum", "multiply", "greet"]);
+/// ```
+pub trait Visitor {
+	/// Visit each expression of a script in order
+	fn visit_expressions(&mut self, expressions: &[Expression]) {
+		expressions.iter().for_each(|expr| self.visit_expression(expr));
+	}
+
+	/// Dispatches to [`visit_value`](Visitor::visit_value), [`visit_word`](Visitor::visit_word) or [`visit_call`](Visitor::visit_call)
+	fn visit_expression(&mut self, expr: &Expression) {
+		walk_expression(self, expr);
+	}
+
+	fn visit_value(&mut self, _value: &Value, _span: &Range<usize>) {}
+
+	fn visit_word(&mut self, _name: &ArcStr, _span: &Range<usize>) {}
+
+	/// Visits each parameter of the call
+	fn visit_call(&mut self, function: &Function, parameters: &[Expression], span: &Range<usize>) {
+		walk_call(self, function, parameters, span);
+	}
+}
+
+/// Default traversal of [`Visitor::visit_expression`]
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expression) {
+	match expr {
+		Expression::Value { value, span } => visitor.visit_value(value, span),
+		Expression::Word { name, span } => visitor.visit_word(name, span),
+		Expression::FnCall { function, parameters, span } => visitor.visit_call(function, parameters, span),
+	}
+}
+
+/// Default traversal of [`Visitor::visit_call`]
+pub fn walk_call<V: Visitor + ?Sized>(visitor: &mut V, _function: &Function, parameters: &[Expression], _span: &Range<usize>) {
+	parameters.iter().for_each(|parameter| visitor.visit_expression(parameter));
+}
+
+/// Traversal of [`Expressions`](Expression) that can modify them in place, or replace them entirely.
+///
+/// Like [`Visitor`], every method defaults to walking into the children of the node.
+/// To transform a tree bottom-up, call [`walk_expression_mut`] before rewriting the expression.
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::expression::{VisitorMut, walk_expression_mut};
+///
+/// // Replace every use of `PI` with it's value
+/// struct Inline;
+///
+/// impl VisitorMut for Inline {
+///     fn visit_expression_mut(&mut self, expr: &mut Expression) {
+///         match expr {
+///             Expression::Word { name, span } if name == "PI" => *expr = Expression::Value { value: 3.14.into(), span: span.clone() },
+///             expr => walk_expression_mut(self, expr),
+///         }
+///     }
+/// }
+///
+/// let mut operators = operators::empty();
+/// operators::minimal(&mut operators);
+///
+/// let mut script = parse("multiply(2, PI, r)", &operators).unwrap();
+/// Inline.visit_expressions_mut(&mut script);
+///
+/// assert_eq!(script[0].to_string(), "multiply(2, 3.14, r)");
+/// ```
+pub trait VisitorMut {
+	/// Visit each expression of a script in order
+	fn visit_expressions_mut(&mut self, expressions: &mut [Expression]) {
+		expressions.iter_mut().for_each(|expr| self.visit_expression_mut(expr));
+	}
+
+	/// Dispatches to [`visit_value_mut`](VisitorMut::visit_value_mut), [`visit_word_mut`](VisitorMut::visit_word_mut) or [`visit_call_mut`](VisitorMut::visit_call_mut)
+	fn visit_expression_mut(&mut self, expr: &mut Expression) {
+		walk_expression_mut(self, expr);
+	}
+
+	fn visit_value_mut(&mut self, _value: &mut Value, _span: &mut Range<usize>) {}
+
+	fn visit_word_mut(&mut self, _name: &mut ArcStr, _span: &mut Range<usize>) {}
+
+	/// Visits each parameter of the call
+	fn visit_call_mut(&mut self, function: &mut Function, parameters: &mut Vec<Expression>, span: &mut Range<usize>) {
+		walk_call_mut(self, function, parameters, span);
+	}
+}
+
+/// Default traversal of [`VisitorMut::visit_expression_mut`]
+pub fn walk_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expression) {
+	match expr {
+		Expression::Value { value, span } => visitor.visit_value_mut(value, span),
+		Expression::Word { name, span } => visitor.visit_word_mut(name, span),
+		Expression::FnCall { function, parameters, span } => visitor.visit_call_mut(function, parameters, span),
+	}
+}
+
+/// Default traversal of [`VisitorMut::visit_call_mut`]
+pub fn walk_call_mut<V: VisitorMut + ?Sized>(visitor: &mut V, _function: &mut Function, parameters: &mut [Expression], _span: &mut Range<usize>) {
+	parameters.iter_mut().for_each(|parameter| visitor.visit_expression_mut(parameter));
 }
 
 /// A function call in an expression, can be user-defined (in Egg) or an [`Operator`] (defined in Rust).
-#[derive(Debug, Clone)]
+/// Host functions keep the name they were invoked with alongside the pointer to the [`Operator`].
+#[derive(Clone)]
 pub enum Function {
-	Host(*const dyn Operator),
+	Host(ArcStr, *const dyn Operator),
 	Script(ArcStr),
 }
 
+impl fmt::Debug for Function {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Function::Host(name, _) => f.debug_tuple("Host").field(name).finish(),
+			Function::Script(name) => f.debug_tuple("Script").field(name).finish(),
+		}
+	}
+}
+
 /// A primitive in Egg; can be a number, boolean, string, function, or an object.
 ///
 /// Most primitives are immutable, to mutate create a new value.
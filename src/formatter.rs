@@ -0,0 +1,312 @@
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+use core::mem::take;
+
+use crate::{
+	error::EggResult,
+	parser::Token,
+	syntax::{self, SyntaxNode},
+};
+
+/// Layout settings for [`format`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+	/// Calls that don't fit within this many columns are broken across lines
+	pub max_width: usize,
+	/// Indentation for each level of nesting
+	pub indent: String,
+}
+
+impl Default for FormatOptions {
+	fn default() -> Self {
+		FormatOptions {
+			max_width: 100,
+			indent: "\t".to_string(),
+		}
+	}
+}
+
+/// Columns occupied by a tab when measuring line width
+const TAB_WIDTH: usize = 4;
+
+/// Formats an Egg script into it's canonical layout, preserving comments from it's [`SyntaxTree`](syntax::SyntaxTree).
+///
+/// - Arguments are separated by `", "`, without trailing commas.
+/// - Calls are kept on one line if they fit within [`FormatOptions::max_width`], otherwise each argument is placed on it's own line.
+///   If the last argument is a `do(...)` or `fn(...)` block, only it is broken, eg: `repeat(10, do(` ... `))`.
+/// - `do(...)` blocks with several expressions are always broken across lines.
+/// - Top-level expressions are placed on their own lines, and runs of blank lines are collapsed into one.
+///
+/// ```
+/// use egglang::formatter::{format, FormatOptions};
+///
+/// let script = "define(x,5)   # five\nrepeat(x, do(println(x) set(x, sum(x -1)) ) )";
+/// let formatted = format(script, &FormatOptions::default()).unwrap();
+///
+/// assert_eq!(formatted, "define(x, 5) # five\nrepeat(x, do(\n\tprintln(x),\n\tset(x, sum(x, -1))\n))\n");
+/// ```
+pub fn format(source: &str, options: &FormatOptions) -> EggResult<String> {
+	let tree = syntax::parse(source)?;
+	let mut builder = Builder {
+		pending: Vec::new(),
+		line_break: true,
+	};
+	let (nodes, dangling) = builder.nodes(&tree.nodes, &tree.trailing);
+
+	let mut printer = Printer {
+		options,
+		out: String::with_capacity(source.len()),
+		column: 0,
+	};
+
+	for (idx, node) in nodes.iter().enumerate() {
+		printer.trivia(&node.leading, 0, idx == 0);
+		printer.node(node, 0, 0);
+		printer.trailing(node);
+		printer.newline();
+	}
+	printer.trivia(&dangling, 0, nodes.is_empty());
+
+	// Blank lines at the end of the script
+	let mut out = printer.out;
+	while out.ends_with("\n\n") {
+		out.pop();
+	}
+
+	Ok(out)
+}
+
+/// Comments and blank lines between expressions
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Trivia {
+	Comment(String),
+	BlankLine,
+}
+
+#[derive(Debug)]
+struct Node {
+	/// Trivia on the lines preceding the node
+	leading: Vec<Trivia>,
+	kind: NodeKind,
+	/// A comment following the node on the same line
+	trailing: Option<String>,
+}
+
+#[derive(Debug)]
+enum NodeKind {
+	/// A word or a literal, kept exactly as written
+	Atom(String),
+	Call {
+		name: String,
+		arguments: Vec<Node>,
+		/// Trivia before the closing bracket
+		dangling: Vec<Trivia>,
+	},
+}
+
+/// Attaches the comments and blank lines of a [`SyntaxTree`] to the nodes they belong to
+struct Builder {
+	pending: Vec<Trivia>,
+	/// Whether a line break was seen since the last token
+	line_break: bool,
+}
+
+impl Builder {
+	/// Comments on the same line as the `previous` node follow it, others are kept pending for the next node
+	fn absorb(&mut self, trivia: &[syntax::Trivia], mut previous: Option<&mut Node>) {
+		for trivia in trivia {
+			match trivia.kind {
+				Token::WhiteSpace => {
+					let breaks = trivia.text.matches('\n').count();
+					self.line_break |= breaks > 0;
+
+					if breaks > 1 && self.pending.last() != Some(&Trivia::BlankLine) {
+						self.pending.push(Trivia::BlankLine);
+					}
+				}
+				Token::Comment => {
+					let comment = trivia.text.trim_end().to_string();
+
+					match previous.as_deref_mut() {
+						Some(node) if !self.line_break && node.trailing.is_none() => node.trailing = Some(comment),
+						_ => self.pending.push(Trivia::Comment(comment)),
+					}
+				}
+				_ => {}
+			}
+		}
+	}
+
+	/// Build a list of nodes, and the trivia between the last node and the `end` of the list
+	fn nodes(&mut self, nodes: &[SyntaxNode], end: &[syntax::Trivia]) -> (Vec<Node>, Vec<Trivia>) {
+		let mut built: Vec<Node> = Vec::with_capacity(nodes.len());
+
+		for node in nodes {
+			self.absorb(node.leading(), built.last_mut());
+			built.push(self.node(node));
+		}
+
+		self.absorb(end, built.last_mut());
+		self.line_break = false;
+		(built, take(&mut self.pending))
+	}
+
+	fn node(&mut self, node: &SyntaxNode) -> Node {
+		let mut leading = take(&mut self.pending);
+		self.line_break = false;
+
+		let kind = match node {
+			SyntaxNode::Atom(token) => NodeKind::Atom(token.text.to_string()),
+			SyntaxNode::Call { name, open, arguments, close } => {
+				// Comments between the name and bracket are moved before the call
+				self.absorb(&open.leading, None);
+				leading.append(&mut self.pending);
+				self.line_break = false;
+
+				let (arguments, dangling) = self.nodes(arguments, &close.leading);
+				NodeKind::Call {
+					name: name.text.to_string(),
+					arguments,
+					dangling,
+				}
+			}
+		};
+
+		Node { leading, kind, trailing: None }
+	}
+}
+
+/// Display width of `text`
+fn width(text: &str) -> usize {
+	text.chars().map(|c| if c == '\t' { TAB_WIDTH } else { 1 }).sum()
+}
+
+/// Render `node` on a single line, if it contains no comments or blank lines
+fn flat(node: &Node) -> Option<String> {
+	match &node.kind {
+		NodeKind::Atom(text) => Some(text.clone()),
+		NodeKind::Call { name, arguments, dangling } => {
+			if !dangling.is_empty() || arguments.iter().any(|arg| !arg.leading.is_empty() || arg.trailing.is_some()) {
+				return None;
+			}
+
+			// Blocks of several expressions are always broken
+			if name == "do" && arguments.len() > 1 {
+				return None;
+			}
+
+			let arguments = arguments.iter().map(flat).collect::<Option<Vec<_>>>()?;
+			Some(format!("{name}({})", arguments.join(", ")))
+		}
+	}
+}
+
+struct Printer<'a> {
+	options: &'a FormatOptions,
+	out: String,
+	column: usize,
+}
+
+impl Printer<'_> {
+	fn write(&mut self, text: &str) {
+		self.out.push_str(text);
+		self.column += width(text);
+	}
+
+	fn newline(&mut self) {
+		self.out.push('\n');
+		self.column = 0;
+	}
+
+	fn indent(&mut self, depth: usize) {
+		for _ in 0..depth {
+			self.out.push_str(&self.options.indent);
+		}
+		self.column += depth * width(&self.options.indent);
+	}
+
+	fn fits(&self, text: &str, suffix: usize) -> bool {
+		self.column + width(text) + suffix <= self.options.max_width
+	}
+
+	/// Print trivia on their own lines. Blank lines are omitted at the start of a block.
+	fn trivia(&mut self, trivia: &[Trivia], depth: usize, first: bool) {
+		for (idx, trivia) in trivia.iter().enumerate() {
+			match trivia {
+				Trivia::BlankLine if first && idx == 0 => {}
+				Trivia::BlankLine => self.newline(),
+				Trivia::Comment(comment) => {
+					self.indent(depth);
+					self.write(comment);
+					self.newline();
+				}
+			}
+		}
+	}
+
+	fn trailing(&mut self, node: &Node) {
+		if let Some(comment) = &node.trailing {
+			self.write(" ");
+			self.write(comment);
+		}
+	}
+
+	/// Print `node` at the current position, leaving room for `suffix` columns after it
+	fn node(&mut self, node: &Node, depth: usize, suffix: usize) {
+		let NodeKind::Call { name, arguments, dangling } = &node.kind else {
+			return self.write(&flat(node).unwrap_or_default());
+		};
+
+		if let Some(flat) = flat(node)
+			&& self.fits(&flat, suffix)
+		{
+			return self.write(&flat);
+		}
+
+		// Break only a trailing block, if the rest fit on this line
+		if let Some((last, rest)) = arguments.split_last()
+			&& let NodeKind::Call { name: last_name, .. } = &last.kind
+			&& matches!(last_name.as_str(), "do" | "fn")
+			&& dangling.is_empty()
+			&& last.leading.is_empty()
+			&& last.trailing.is_none()
+			&& let Some(rest) = rest
+				.iter()
+				.map(|arg| if arg.leading.is_empty() && arg.trailing.is_none() { flat(arg) } else { None })
+				.collect::<Option<Vec<_>>>()
+		{
+			let separator = if rest.is_empty() { "" } else { ", " };
+			let prefix = format!("{name}({}{separator}", rest.join(", "));
+
+			if self.fits(&prefix, last_name.len() + 1) {
+				self.write(&prefix);
+				self.node(last, depth, suffix + 1);
+				return self.write(")");
+			}
+		}
+
+		self.write(name);
+		self.write("(");
+		self.newline();
+
+		for (idx, argument) in arguments.iter().enumerate() {
+			let last = idx + 1 == arguments.len();
+
+			self.trivia(&argument.leading, depth + 1, idx == 0);
+			self.indent(depth + 1);
+			self.node(argument, depth + 1, if last { 0 } else { 1 });
+			if !last {
+				self.write(",");
+			}
+			self.trailing(argument);
+			self.newline();
+		}
+
+		self.trivia(dangling, depth + 1, arguments.is_empty());
+		self.indent(depth);
+		self.write(")");
+	}
+}
@@ -0,0 +1,177 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::ops::Range;
+
+use crate::{
+	error::{EggError, EggResult},
+	expression::{Expression, Value},
+	scope::Scope,
+};
+
+/// Callbacks invoked as a script executes. Implement only the methods of interest, the rest default to doing nothing.
+///
+/// Hooks are installed on a [`Scope`] using [`Scope::add_hook`]. While a hook runs, no hooks are installed,
+/// so evaluating expressions with the provided `scope` from within a hook does not trigger further callbacks.
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::hooks::Hook;
+/// use std::{cell::RefCell, rc::Rc};
+///
+/// // Records the name of every variable defined
+/// struct Definitions(Rc<RefCell<Vec<String>>>);
+///
+/// impl Hook for Definitions {
+///     fn on_define(&mut self, name: &str, _: &Value, _: &mut Scope) {
+///         self.0.borrow_mut().push(name.to_string());
+///     }
+/// }
+///
+/// let mut operators = operators::empty();
+/// operators::minimal(&mut operators);
+///
+/// let names = Rc::new(RefCell::new(Vec::new()));
+/// let mut scope = Scope::default();
+/// scope.add_hook(Box::new(Definitions(names.clone())));
+///
+/// let script = parse("define(x, 5) define(y, sum(x 1))", &operators).unwrap();
+/// script.iter().for_each(|expr| drop(evaluate(expr, &mut scope)));
+///
+/// assert_eq!(*names.borrow(), ["x", "y"]);
+/// ```
+#[allow(unused_variables)]
+pub trait Hook {
+	/// An expression is about to be evaluated
+	fn on_enter(&mut self, expr: &Expression, scope: &mut Scope) {}
+	/// An expression has been evaluated
+	fn on_exit(&mut self, expr: &Expression, result: &EggResult<Value>, scope: &mut Scope) {}
+	/// A function defined in Egg is about to be called, with it's arguments already evaluated
+	fn on_call(&mut self, name: &str, arguments: &[Value], scope: &mut Scope) {}
+	/// A function defined in Egg has returned
+	fn on_return(&mut self, name: &str, result: &EggResult<Value>, scope: &mut Scope) {}
+	/// A new variable is being defined in the current scope
+	fn on_define(&mut self, name: &str, value: &Value, scope: &mut Scope) {}
+	/// An existing variable is being updated
+	fn on_set(&mut self, name: &str, value: &Value, scope: &mut Scope) {}
+	/// A variable is about to be deleted
+	fn on_delete(&mut self, name: &str, scope: &mut Scope) {}
+	/// An error was raised while evaluating `expr`. Only invoked for the innermost expression, not as the error propagates.
+	fn on_error(&mut self, error: &EggError, expr: &Expression, scope: &mut Scope) {}
+	/// The script called `panic(value)` at `location`, before [`EggError::ScriptPanic`] propagates.
+	/// Hosts which want panics to be fatal can abort here, see [`Fatal`].
+	fn on_panic(&mut self, value: &Value, location: Range<usize>, scope: &mut Scope) {}
+}
+
+/// Makes `panic(...)` in scripts panic the host, rather than only raising [`EggError::ScriptPanic`]
+///
+/// ```should_panic
+/// use egglang::prelude::*;
+/// use egglang::hooks::Fatal;
+///
+/// let mut operators = operators::empty();
+/// operators::minimal(&mut operators);
+///
+/// let mut scope = Scope::default();
+/// scope.add_hook(Box::new(Fatal));
+///
+/// let script = parse("panic(\"unreachable state\")", &operators).unwrap();
+/// let _ = evaluate(&script[0], &mut scope);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Fatal;
+
+impl Hook for Fatal {
+	fn on_panic(&mut self, value: &Value, location: Range<usize>, _: &mut Scope) {
+		match value {
+			Value::String(message) => panic!("{message} (at {location:?})"),
+			value => panic!("Script panicked with {value} (at {location:?})"),
+		}
+	}
+}
+
+/// Hooks installed on a scope
+#[derive(Default)]
+pub(crate) struct Hooks {
+	installed: Vec<Box<dyn Hook>>,
+	/// Set once an error has been reported to hooks, until the next expression is entered
+	unwinding: bool,
+	/// Span of the innermost expression which raised an error, until it is taken
+	origin: Option<Range<usize>>,
+}
+
+impl core::fmt::Debug for Hooks {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		write!(f, "Hooks ({})", self.installed.len())
+	}
+}
+
+impl Scope {
+	/// Install a hook, to be invoked after any hooks already installed
+	pub fn add_hook(&mut self, hook: Box<dyn Hook>) {
+		self.extras_mut().hooks.installed.push(hook);
+	}
+
+	/// Remove and return all installed hooks
+	pub fn take_hooks(&mut self) -> Vec<Box<dyn Hook>> {
+		core::mem::take(&mut self.extras_mut().hooks.installed)
+	}
+
+	/// Invoke `callback` on every installed hook. Hooks are detached from the scope for the duration.
+	#[inline]
+	pub(crate) fn run_hooks(&mut self, mut callback: impl FnMut(&mut dyn Hook, &mut Scope)) {
+		if self.extras().hooks.installed.is_empty() {
+			return;
+		}
+
+		let mut hooks = self.take_hooks();
+		hooks.iter_mut().for_each(|hook| callback(hook.as_mut(), self));
+
+		// Preserve any hooks installed while the others were running
+		let added = core::mem::replace(&mut self.extras_mut().hooks.installed, hooks);
+		self.extras_mut().hooks.installed.extend(added);
+	}
+
+	#[inline]
+	pub(crate) fn hook_enter(&mut self, expr: &Expression) {
+		if self.extras().hooks.installed.is_empty() {
+			return;
+		}
+
+		self.extras_mut().hooks.unwinding = false;
+		self.run_hooks(|hook, scope| hook.on_enter(expr, scope));
+	}
+
+	#[inline]
+	pub(crate) fn hook_exit(&mut self, expr: &Expression, result: &EggResult<Value>) {
+		if let Err(error) = result {
+			self.hook_error(error, expr);
+		}
+
+		self.run_hooks(|hook, scope| hook.on_exit(expr, result, scope));
+	}
+
+	fn hook_error(&mut self, error: &EggError, expr: &Expression) {
+		let hooks = &mut self.extras_mut().hooks;
+
+		// The innermost expression is the first to exit with the error
+		if hooks.origin.is_none() {
+			hooks.origin = Some(expr.span());
+		}
+
+		if hooks.installed.is_empty() || hooks.unwinding {
+			return;
+		}
+
+		hooks.unwinding = true;
+		self.run_hooks(|hook, scope| hook.on_error(error, expr, scope));
+	}
+
+	#[inline]
+	pub(crate) fn hook_panic(&mut self, value: &Value, location: Range<usize>) {
+		self.run_hooks(|hook, scope| hook.on_panic(value, location.clone(), scope));
+	}
+
+	/// Span of the innermost expression which raised an error since the origin was last taken
+	pub(crate) fn take_error_origin(&mut self) -> Option<Range<usize>> {
+		self.extras_mut().hooks.origin.take()
+	}
+}
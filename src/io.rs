@@ -0,0 +1,221 @@
+use alloc::{
+	boxed::Box,
+	collections::{BTreeMap, VecDeque},
+	rc::Rc,
+	string::String,
+};
+use core::cell::RefCell;
+
+use crate::{
+	error::{EggError, EggResult},
+	evaluator::evaluate,
+	expression::Value,
+	operators::Operator,
+	parser::parse,
+	scope::Scope,
+};
+
+/// Where `print` and `println` write to, installed on a [`Scope`] using [`Scope::set_output`].
+///
+/// Without an installed output scripts write to [`Stdout`], or fail to print in `no_std` builds.
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::io::OutputBuffer;
+///
+/// let mut operators = operators::empty();
+/// operators::full(&mut operators);
+///
+/// let output = OutputBuffer::default();
+/// let mut scope = Scope::default();
+/// scope.set_output(Box::new(output.clone()));
+///
+/// let script = parse("print(1, 2) println(\" done\")", &operators).unwrap();
+/// script.iter().for_each(|expr| drop(evaluate(expr, &mut scope).unwrap()));
+///
+/// assert_eq!(output.take(), "1 2 done\n");
+/// ```
+pub trait Output {
+	/// Write text printed by the script
+	fn write(&mut self, text: &str) -> EggResult<()>;
+}
+
+/// Where `readline` reads from, installed on a [`Scope`] using [`Scope::set_input`].
+///
+/// Without an installed input scripts read from [`Stdin`], or fail to read in `no_std` builds.
+pub trait Input {
+	/// Read the next line, without it's line ending. `None` once the input has ended.
+	fn read_line(&mut self) -> EggResult<Option<String>>;
+}
+
+/// Writes to the process's standard output
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stdout;
+
+#[cfg(feature = "std")]
+impl Output for Stdout {
+	fn write(&mut self, text: &str) -> EggResult<()> {
+		use std::io::Write;
+
+		let mut stdout = std::io::stdout().lock();
+		stdout
+			.write_all(text.as_bytes())
+			.and_then(|_| stdout.flush())
+			.map_err(|err| EggError::OperatorComplaint(err.to_string()))
+	}
+}
+
+/// Reads from the process's standard input
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stdin;
+
+#[cfg(feature = "std")]
+impl Input for Stdin {
+	fn read_line(&mut self) -> EggResult<Option<String>> {
+		let mut line = String::new();
+		match std::io::stdin().read_line(&mut line) {
+			Ok(0) => Ok(None),
+			Ok(_) => Ok(Some(line.trim_end_matches(['\r', '\n']).into())),
+			Err(err) => Err(EggError::OperatorComplaint(err.to_string())),
+		}
+	}
+}
+
+/// Collects output in memory. Clones share the same buffer, so one can be installed while the host keeps another.
+#[derive(Debug, Clone, Default)]
+pub struct OutputBuffer(Rc<RefCell<String>>);
+
+impl OutputBuffer {
+	/// Everything written so far
+	pub fn contents(&self) -> String {
+		self.0.borrow().clone()
+	}
+
+	/// Everything written so far, emptying the buffer
+	pub fn take(&self) -> String {
+		self.0.take()
+	}
+}
+
+impl Output for OutputBuffer {
+	fn write(&mut self, text: &str) -> EggResult<()> {
+		self.0.borrow_mut().push_str(text);
+		Ok(())
+	}
+}
+
+/// Provides input from memory, one line at a time
+#[derive(Debug, Clone, Default)]
+pub struct InputBuffer {
+	lines: VecDeque<String>,
+}
+
+impl InputBuffer {
+	/// Input consisting of the lines of `text`
+	pub fn new(text: &str) -> InputBuffer {
+		InputBuffer {
+			lines: text.lines().map(String::from).collect(),
+		}
+	}
+}
+
+impl Input for InputBuffer {
+	fn read_line(&mut self) -> EggResult<Option<String>> {
+		Ok(self.lines.pop_front())
+	}
+}
+
+/// Output and input installed on a scope
+#[derive(Default)]
+pub(crate) struct Streams {
+	output: Option<Box<dyn Output>>,
+	input: Option<Box<dyn Input>>,
+}
+
+impl core::fmt::Debug for Streams {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		write!(f, "Streams (output: {}, input: {})", self.output.is_some(), self.input.is_some())
+	}
+}
+
+impl Scope {
+	/// Install where the script's output is written, returning the previously installed output
+	pub fn set_output(&mut self, output: Box<dyn Output>) -> Option<Box<dyn Output>> {
+		self.extras_mut().streams.output.replace(output)
+	}
+
+	/// Remove the installed output, reverting to the default
+	pub fn take_output(&mut self) -> Option<Box<dyn Output>> {
+		self.extras_mut().streams.output.take()
+	}
+
+	/// Install where the script's input is read from, returning the previously installed input
+	pub fn set_input(&mut self, input: Box<dyn Input>) -> Option<Box<dyn Input>> {
+		self.extras_mut().streams.input.replace(input)
+	}
+
+	/// Remove the installed input, reverting to the default
+	pub fn take_input(&mut self) -> Option<Box<dyn Input>> {
+		self.extras_mut().streams.input.take()
+	}
+
+	/// Write to the installed output
+	pub(crate) fn write_output(&mut self, text: &str) -> EggResult<()> {
+		match &mut self.extras_mut().streams.output {
+			Some(output) => output.write(text),
+			#[cfg(feature = "std")]
+			None => Stdout.write(text),
+			#[cfg(not(feature = "std"))]
+			None => Err(EggError::OperatorComplaint("No output is installed on the scope".into())),
+		}
+	}
+
+	/// Read a line from the installed input
+	pub(crate) fn read_input(&mut self) -> EggResult<Option<String>> {
+		match &mut self.extras_mut().streams.input {
+			Some(input) => input.read_line(),
+			#[cfg(feature = "std")]
+			None => Stdin.read_line(),
+			#[cfg(not(feature = "std"))]
+			None => Err(EggError::OperatorComplaint("No input is installed on the scope".into())),
+		}
+	}
+}
+
+/// Everything a script printed, and how it ended, see [`capture`]
+#[derive(Debug, Clone)]
+pub struct Captured {
+	pub output: String,
+	/// Value of the last expression, or the error which stopped the script, including parsing errors
+	pub result: EggResult<Value>,
+}
+
+/// Parse and run a script in a fresh [`Scope`], capturing it's output instead of writing to stdout.
+///
+/// The script reads lines of `input` with `readline`. Output printed before an error is kept.
+///
+/// ```
+/// use egglang::prelude::*;
+///
+/// let mut operators = operators::empty();
+/// operators::full(&mut operators);
+///
+/// let captured = egglang::io::capture("println(\"Hello \", readline()) sum(1, 2)", &operators, "Egg");
+/// assert_eq!(captured.output, "Hello Egg\n");
+/// assert_eq!(captured.result.unwrap(), Value::from(3.0));
+///
+/// let captured = egglang::io::capture("println(\"before\") assert(false, \"failed\") println(\"after\")", &operators, "");
+/// assert_eq!(captured.output, "before\n");
+/// assert!(matches!(captured.result, Err(EggError::AssertionFailed(_))));
+/// ```
+pub fn capture(source: &str, operators: &BTreeMap<&'static str, Box<dyn Operator>>, input: &str) -> Captured {
+	let output = OutputBuffer::default();
+	let mut scope = Scope::default();
+	scope.set_output(Box::new(output.clone()));
+	scope.set_input(Box::new(InputBuffer::new(input)));
+
+	let result = parse(source, operators).and_then(|expressions| expressions.iter().try_fold(Value::Nil, |_, expr| evaluate(expr, &mut scope)));
+	Captured { output: output.take(), result }
+}
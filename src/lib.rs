@@ -20,15 +20,39 @@ pub mod prelude {
 #[cfg(test)]
 mod tests;
 
+/// A step [`Debugger`](debugger::Debugger) with breakpoints, built on [`Hooks`](hooks::Hook)
+pub mod debugger;
+/// Extracts `##` doc comments from script [`definitions`](docs::definitions), and renders reference pages from them
+pub mod docs;
 /// Error and Result types
 pub mod error;
 /// [`Evaluates`](expression::Expression) an expression into a [`Value`](expression::Value)
 pub mod evaluator;
 /// [`Expression`](expression::Expression) and [`Value`](expression::Value) types
 pub mod expression;
+/// Canonical [`Formatter`](formatter::format) for Egg scripts, preserving comments
+pub mod formatter;
+/// [`Hooks`](hooks::Hook) for observing script execution
+pub mod hooks;
+/// [`Output`](io::Output) and [`Input`](io::Input) of the console builtins, installed on a [`Scope`](scope::Scope)
+pub mod io;
+/// Static [`Linter`](lint::Linter) reporting common mistakes in parsed scripts
+pub mod lint;
 /// Traits for defining functions in Rust callable in Egg, as well as several builtin functions
 pub mod operators;
+/// Constant folding and simplification of parsed scripts, see [`optimize`](optimizer::optimize)
+pub mod optimizer;
 /// [`Parser`](parser::parse) for Egg scripts
 pub mod parser;
+/// Serialization of parsed scripts, to [`load`](precompiled::from_bytes) them without parsing
+pub mod precompiled;
+/// Instrumenting profiler, timing every call to Egg functions and builtins rather than sampling, see [`Scope::start_profiling`](scope::Scope::start_profiling)
+#[cfg(feature = "std")]
+pub mod profiler;
+/// Run tests written in Egg with a [`TestRunner`](testing::TestRunner), capturing their output
+#[cfg(feature = "std")]
+pub mod testing;
+/// Lossless [`SyntaxTree`](syntax::SyntaxTree) of Egg scripts, preserving comments and whitespace
+pub mod syntax;
 /// Contains the [`Scope`](scope::Scope) struct, which stores variables and allows for creation of local scopes
 pub mod scope;
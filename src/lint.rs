@@ -0,0 +1,299 @@
+use alloc::{
+	collections::{BTreeMap, BTreeSet},
+	format,
+	string::String,
+	vec::Vec,
+};
+use arcstr::ArcStr;
+use core::{fmt, mem, ops::Range};
+
+use crate::{
+	expression::{Expression, Function, Value, Visitor, walk_call},
+	scope::Scope,
+};
+
+/// Mistakes detected by the [`Linter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rule {
+	/// `set` on a name that is never `define`d
+	UndefinedSet,
+	/// `define` of a name already defined in the same scope, which errors at runtime
+	Redefinition,
+	/// A call to a function that is never defined
+	UndefinedFunction,
+	/// A variable that is defined but never used. Names starting with `_` are exempt.
+	UnusedVariable,
+	/// An `if` or `while` whose condition is a constant, leaving a branch unreachable
+	ConstantCondition,
+}
+
+impl Rule {
+	/// Every rule, all are enabled by default
+	pub const ALL: [Rule; 5] = [Rule::UndefinedSet, Rule::Redefinition, Rule::UndefinedFunction, Rule::UnusedVariable, Rule::ConstantCondition];
+
+	/// The rule's name in kebab-case, eg: `unused-variable`
+	pub fn name(&self) -> &'static str {
+		match self {
+			Rule::UndefinedSet => "undefined-set",
+			Rule::Redefinition => "redefinition",
+			Rule::UndefinedFunction => "undefined-function",
+			Rule::UnusedVariable => "unused-variable",
+			Rule::ConstantCondition => "constant-condition",
+		}
+	}
+
+	/// Find a rule by it's [`name`](Rule::name)
+	pub fn from_name(name: &str) -> Option<Rule> {
+		Rule::ALL.into_iter().find(|rule| rule.name() == name)
+	}
+}
+
+/// A problem found by the [`Linter`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+	pub rule: Rule,
+	pub message: String,
+	/// Span of the offending expression in the script
+	pub span: Range<usize>,
+}
+
+impl fmt::Display for Warning {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} [{}]", self.message, self.rule.name())
+	}
+}
+
+/// Static analysis of parsed scripts, reporting common mistakes as [`Warnings`](Warning).
+///
+/// Only `fn(...)` introduces a new scope, though the branches of an `if` may each define the same names. Names are resolved regardless of the order of definitions,
+/// as functions may refer to variables defined after them.
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::lint::{Linter, Rule};
+///
+/// let mut operators = operators::empty();
+/// operators::minimal(&mut operators);
+///
+/// let script = parse("define(x, 5)\nset(y, 2)\nif(True, sum(x, 1), greet())", &operators).unwrap();
+/// let warnings = Linter::default().lint(&script);
+///
+/// let rules = warnings.iter().map(|w| w.rule).collect::<Vec<_>>();
+/// assert_eq!(rules, [Rule::UndefinedSet, Rule::ConstantCondition, Rule::UndefinedFunction]);
+/// assert_eq!(warnings[0].span, 17..18);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Linter {
+	rules: BTreeSet<Rule>,
+	globals: BTreeSet<ArcStr>,
+}
+
+impl Default for Linter {
+	/// All rules enabled, with the constants of [`Scope::default`] as globals
+	fn default() -> Linter {
+		Linter {
+			rules: Rule::ALL.into_iter().collect(),
+			globals: Scope::default().global_bindings().keys().cloned().collect(),
+		}
+	}
+}
+
+impl Linter {
+	/// Stop reporting `rule`
+	pub fn allow(&mut self, rule: Rule) {
+		self.rules.remove(&rule);
+	}
+
+	/// Report `rule`
+	pub fn deny(&mut self, rule: Rule) {
+		self.rules.insert(rule);
+	}
+
+	/// Declare a variable or function provided by the host, eg: defined on the [`Scope`] before the script runs
+	pub fn add_global(&mut self, name: impl Into<ArcStr>) {
+		self.globals.insert(name.into());
+	}
+
+	/// Check a script, returning warnings in the order they appear
+	pub fn lint(&self, expressions: &[Expression]) -> Vec<Warning> {
+		let mut analysis = Analysis {
+			linter: self,
+			scopes: Vec::new(),
+			warnings: Vec::new(),
+		};
+
+		analysis.enter(BTreeMap::new(), expressions);
+		analysis.visit_expressions(expressions);
+		analysis.exit();
+
+		let mut warnings = analysis.warnings;
+		warnings.retain(|warning| self.rules.contains(&warning.rule));
+		warnings.sort_by_key(|warning| warning.span.start);
+		warnings
+	}
+}
+
+struct Binding {
+	span: Range<usize>,
+	used: bool,
+}
+
+struct Analysis<'a> {
+	linter: &'a Linter,
+	/// Variables defined in each scope, the innermost is last
+	scopes: Vec<BTreeMap<ArcStr, Binding>>,
+	warnings: Vec<Warning>,
+}
+
+fn function_name(function: &Function) -> &ArcStr {
+	match function {
+		Function::Host(name, _) | Function::Script(name) => name,
+	}
+}
+
+impl Analysis<'_> {
+	fn warn(&mut self, rule: Rule, message: String, span: Range<usize>) {
+		self.warnings.push(Warning { rule, message, span });
+	}
+
+	/// Enter a new scope, with it's `parameters` and the variables defined by `body`
+	fn enter(&mut self, parameters: BTreeMap<ArcStr, Binding>, body: &[Expression]) {
+		self.scopes.push(parameters);
+		Declare(self).visit_expressions(body);
+	}
+
+	/// Leave the current scope, reporting unused variables
+	fn exit(&mut self) {
+		let scope = self.scopes.pop().unwrap_or_default();
+
+		for (name, binding) in scope {
+			if !binding.used && !name.starts_with('_') {
+				self.warn(Rule::UnusedVariable, format!("Variable `{name}` is never used"), binding.span);
+			}
+		}
+	}
+
+	/// The innermost scope, where definitions are recorded
+	fn current(&mut self) -> &mut BTreeMap<ArcStr, Binding> {
+		self.scopes.last_mut().expect("A scope is always entered")
+	}
+
+	/// Mark the closest variable called `name` as used, returning whether it exists
+	fn resolve(&mut self, name: &str) -> bool {
+		for scope in self.scopes.iter_mut().rev() {
+			if let Some(binding) = scope.get_mut(name) {
+				binding.used = true;
+				return true;
+			}
+		}
+
+		self.linter.globals.contains(name)
+	}
+
+	fn constant(&self, expr: &Expression) -> Option<bool> {
+		match expr {
+			Expression::Value { value: Value::Boolean(b), .. } => Some(*b),
+			Expression::Value { value: Value::Number(n), .. } => Some(n.0 != 0.0),
+			// Constants from the default scope, unless shadowed
+			Expression::Word { name, .. } if matches!(name.as_str(), "true" | "false") && !self.scopes.iter().any(|scope| scope.contains_key(name)) => Some(name == "true"),
+			_ => None,
+		}
+	}
+}
+
+/// Records definitions in the current scope of an [`Analysis`], without entering function bodies
+struct Declare<'a, 'b>(&'b mut Analysis<'a>);
+
+impl Visitor for Declare<'_, '_> {
+	fn visit_call(&mut self, function: &Function, parameters: &[Expression], span: &Range<usize>) {
+		match (function_name(function).as_str(), parameters) {
+			("fn", _) => return,
+			("define", [Expression::Word { name, span }, ..]) => match self.0.current().contains_key(name) {
+				true => self.0.warn(Rule::Redefinition, format!("`{name}` is already defined in this scope"), span.clone()),
+				false => drop(self.0.current().insert(name.clone(), Binding { span: span.clone(), used: false })),
+			},
+			("if", [condition, branches @ ..]) => {
+				self.visit_expression(condition);
+
+				// Only one branch runs, so each may define the same names
+				let mut defined = BTreeMap::new();
+				for branch in branches {
+					let before = self.0.current().keys().cloned().collect::<BTreeSet<_>>();
+					self.visit_expression(branch);
+
+					let scope = self.0.current();
+					let (existing, added): (BTreeMap<_, _>, BTreeMap<_, _>) = mem::take(scope).into_iter().partition(|(name, _)| before.contains(name));
+					*scope = existing;
+					for (name, binding) in added {
+						defined.entry(name).or_insert(binding);
+					}
+				}
+
+				self.0.current().extend(defined);
+				return;
+			}
+			// Deleted names may be defined again
+			("delete", [Expression::Word { name, .. }]) => drop(self.0.current().remove(name)),
+			_ => {}
+		}
+
+		walk_call(self, function, parameters, span);
+	}
+}
+
+/// Checks expressions against the scopes declared so far
+impl Visitor for Analysis<'_> {
+	fn visit_word(&mut self, name: &ArcStr, _span: &Range<usize>) {
+		self.resolve(name);
+	}
+
+	fn visit_call(&mut self, function: &Function, parameters: &[Expression], span: &Range<usize>) {
+		let name = function_name(function);
+		match (function, name.as_str(), parameters) {
+			(Function::Host(..), "fn", [arguments @ .., body]) => {
+				let arguments = arguments.iter().filter_map(|argument| match argument {
+					Expression::Word { name, span } => Some((name.clone(), Binding { span: span.clone(), used: true })),
+					_ => None,
+				});
+
+				self.enter(arguments.collect(), core::slice::from_ref(body));
+				self.visit_expression(body);
+				self.exit();
+				return;
+			}
+			(Function::Host(..), "define", [Expression::Word { .. }, rest @ ..]) => {
+				self.visit_expressions(rest);
+				return;
+			}
+			(Function::Host(..), "set", [Expression::Word { name, span }, rest @ ..]) => {
+				// Assignments don't count as using a variable
+				let defined = self.scopes.iter().any(|scope| scope.contains_key(name)) || self.linter.globals.contains(name);
+				if !defined {
+					self.warn(Rule::UndefinedSet, format!("`{name}` is set but never defined"), span.clone());
+				}
+
+				self.visit_expressions(rest);
+				return;
+			}
+			(Function::Host(..), "if", [condition, ..]) => {
+				if let Some(constant) = self.constant(condition) {
+					let unreachable = if constant { "else" } else { "then" };
+					self.warn(
+						Rule::ConstantCondition,
+						format!("Condition is always {constant}, the {unreachable} branch is unreachable"),
+						condition.span(),
+					);
+				}
+			}
+			(Function::Host(..), "while", [condition, ..]) if self.constant(condition) == Some(false) => {
+				self.warn(Rule::ConstantCondition, "Condition is always false, the loop body is unreachable".into(), condition.span());
+			}
+			(Function::Script(..), ..) if !self.resolve(name) => {
+				self.warn(Rule::UndefinedFunction, format!("Function `{name}` is never defined"), span.start..span.start + name.len());
+			}
+			_ => {}
+		}
+
+		walk_call(self, function, parameters, span);
+	}
+}
@@ -22,6 +22,14 @@ impl Operator for Sum {
 			.try_fold(0.0, |acc, d| d.map(|d| acc + d.0))
 			.map(|f| f.into())
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("sum(numbers...)\n\nAdds all of it's arguments together.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
 }
 
 // Basic multiply operation
@@ -39,6 +47,14 @@ impl Operator for Multiply {
 			.try_fold(1.0, |acc, d| d.map(|d| acc * d.0))
 			.map(|f| f.into())
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("multiply(numbers...)\n\nMultiplies all of it's arguments together.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
 }
 
 // Basic minus operation
@@ -56,6 +72,18 @@ impl Operator for Subtract {
 			(a, b) => Err(EggError::OperatorComplaint(format!("Arguments: {a}, {b} must both be numbers"))),
 		}
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("subtract(a, b)\n\nSubtracts `b` from `a`.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(2)
+	}
 }
 
 // Basic divide operation
@@ -73,6 +101,18 @@ impl Operator for Divide {
 			(a, b) => Err(EggError::OperatorComplaint(format!("Arguments: {a}, {b} must both be numbers"))),
 		}
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("divide(a, b)\n\nDivides `a` by `b`.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(2)
+	}
 }
 
 // Basic modulus operation
@@ -90,4 +130,16 @@ impl Operator for Modulus {
 			(a, b) => Err(EggError::OperatorComplaint(format!("Arguments: {a}, {b} must both be numbers"))),
 		}
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("modulus(a, b)\n\nThe remainder of dividing `a` by `b`.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(2)
+	}
 }
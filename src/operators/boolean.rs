@@ -26,6 +26,18 @@ impl Operator for AND {
 			_ => Err(EggError::OperatorComplaint(NOT_BOOLEAN.to_string())),
 		}
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("and(a, b)\n\nTrue if both booleans are True.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(2)
+	}
 }
 
 // AND
@@ -43,6 +55,18 @@ impl Operator for OR {
 			_ => Err(EggError::OperatorComplaint(NOT_BOOLEAN.to_string())),
 		}
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("or(a, b)\n\nTrue if either boolean is True.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(2)
+	}
 }
 
 // AND
@@ -56,4 +80,16 @@ impl Operator for NOT {
 			_ => Err(EggError::OperatorComplaint(NOT_BOOLEAN.to_string())),
 		}
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("not(a)\n\nInverts a boolean.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(1)
+	}
 }
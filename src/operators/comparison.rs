@@ -18,6 +18,18 @@ impl super::Operator for Equals {
 
 		Ok((val1 == val2).into())
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("equals(a, b)\n\nTrue if both values are equal.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(2)
+	}
 }
 
 // Checks for inequality
@@ -32,6 +44,18 @@ impl super::Operator for NotEquals {
 
 		Ok((val1 != val2).into())
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("not_equals(a, b)\n\nTrue if the values are not equal.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(2)
+	}
 }
 
 // Greater than
@@ -49,6 +73,18 @@ impl super::Operator for GreaterThan {
 			_ => Err(EggError::OperatorComplaint("please provide numbers as arguments for mathematical operations".to_string())),
 		}
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("greater_than(a, b)\n\nTrue if the number `a` is greater than `b`.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(2)
+	}
 }
 
 /// Lesser than
@@ -66,6 +102,18 @@ impl super::Operator for LessThan {
 			_ => Err(EggError::OperatorComplaint("please provide numbers as arguments for mathematical operations".to_string())),
 		}
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("less_than(a, b)\n\nTrue if the number `a` is less than `b`.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(2)
+	}
 }
 
 /// Checks if the value is nil
@@ -77,4 +125,16 @@ impl super::Operator for IsNil {
 
 		Ok(matches!(evaluate(&args[0], scope)?, Value::Nil).into())
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("is_nil(value)\n\nTrue if the value is Nil.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(1)
+	}
 }
@@ -1,30 +1,51 @@
 use super::Operator;
 use crate::{
-	error::{EggError, EggResult},
+	error::EggResult,
 	evaluator::evaluate,
 	expression::{Expression, Value},
 	scope::Scope,
 };
+use alloc::string::String;
+
+/// Render `values` as printed by `print` and `println`, evaluating each argument
+pub(crate) fn display(args: &[Expression], separator: &str, scope: &mut Scope) -> EggResult<String> {
+	use core::fmt::Write;
+
+	let mut out = String::new();
+	for (idx, arg) in args.iter().enumerate() {
+		if idx > 0 {
+			out.push_str(separator);
+		}
+
+		let _ = match evaluate(arg, scope)? {
+			Value::Number(num) => write!(out, "{num}"),
+			Value::String(string) => write!(out, "{string}"),
+			Value::Nil => write!(out, "Nil"),
+			Value::Boolean(b) => write!(out, "{}", if b { "True" } else { "False" }),
+			Value::Function(idx) => write!(out, "{:?}", scope.get_function_definition(idx)?),
+			// Objects end their own line
+			Value::Object(tag) => writeln!(out, "{:?}", scope.get_object(tag)),
+		};
+	}
+
+	Ok(out)
+}
 
 // Prints it's data and a newline
 pub struct PrintLine;
 
 impl Operator for PrintLine {
 	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value> {
-		for arg in args {
-			match evaluate(arg, scope)? {
-				Value::Number(num) => print!("{num}"),
-				Value::String(string) => print!("{string}"),
-				Value::Nil => print!("Nil"),
-				Value::Boolean(b) => print!("{}", if b { "True" } else { "False" }),
-				Value::Function(idx) => print!("{:?}", scope.get_function_definition(idx)?),
-				Value::Object(tag) => println!("{:?}", scope.get_object(tag)),
-			}
-		}
+		let mut text = display(args, "", scope)?;
+		text.push('\n');
 
-		println!();
+		scope.write_output(&text)?;
 		Ok(Value::Nil)
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("println(values...)\n\nPrints it's arguments followed by a newline.")
+	}
 }
 
 // Prints it's arguments without a newline
@@ -32,24 +53,14 @@ pub struct Print;
 
 impl Operator for Print {
 	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value> {
-		let len = args.len();
-		for (idx, arg) in args.iter().enumerate() {
-			match evaluate(arg, scope)? {
-				Value::Number(num) => print!("{num}"),
-				Value::String(string) => print!("{string}"),
-				Value::Nil => print!("Nil"),
-				Value::Boolean(b) => print!("{}", if b { "True" } else { "False" }),
-				Value::Function(idx) => print!("{:?}", scope.get_function_definition(idx)?),
-				Value::Object(tag) => println!("{:?}", scope.get_object(tag)),
-			}
-
-			if len > 1 && idx != len - 1 {
-				print!(" ");
-			}
-		}
-
+		let text = display(args, " ", scope)?;
+		scope.write_output(&text)?;
 		Ok(Value::Nil)
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("print(values...)\n\nPrints it's arguments separated by spaces.")
+	}
 }
 
 /// Reads a line of input from the console
@@ -58,14 +69,19 @@ pub struct ReadLine;
 impl Operator for ReadLine {
 	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value> {
 		// Print prompt if any
-		if let Some(prompt) = args.get(0) {
+		if let Some(prompt) = args.first() {
 			Print.evaluate(core::slice::from_ref(prompt), scope)?;
 		}
 
-		// read line
-		let mut input = String::new();
-		std::io::stdin().read_line(&mut input).map_err(|err| EggError::OperatorComplaint(err.to_string()))?;
+		// Empty once the input has ended
+		let input = scope.read_input()?.unwrap_or_default();
+		let input = input.trim();
+		scope.allocate_string(input.len())?;
+
+		Ok(input.into())
+	}
 
-		Ok(input.trim().into())
+	fn documentation(&self) -> Option<&'static str> {
+		Some("readline(prompt?)\n\nReads a line of input, after printing an optional prompt.")
 	}
 }
@@ -14,6 +14,14 @@ impl Operator for Do {
 	fn evaluate(&self, args: &[expression::Expression], scope: &mut Scope) -> EggResult<Value> {
 		args.iter().try_fold(Value::Nil, |_, nxt| evaluate(nxt, scope))
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("do(expressions...)\n\nEvaluates each expression in order, yielding the value of the last.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
 }
 
 // Simple if statement
@@ -43,6 +51,18 @@ impl Operator for If {
 			evaluate(&args[2], scope)
 		}
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("if(condition, then, else)\n\nEvaluates `then` if the condition is True or a non-zero number, otherwise `else`.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(3)
+	}
 }
 
 // Simple while loop
@@ -57,6 +77,7 @@ impl Operator for While {
 		let mut loop_result = Value::Nil;
 
 		loop {
+			scope.check_interrupts()?;
 			let condition = evaluate(&args[0], scope)?;
 
 			let continue_condition = match condition {
@@ -74,6 +95,10 @@ impl Operator for While {
 			loop_result = evaluate(&args[1], scope)?;
 		}
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("while(condition, body)\n\nEvaluates `body` for as long as `condition` is True, yielding the last value of `body`.")
+	}
 }
 
 // Simpler loop construct
@@ -99,12 +124,18 @@ impl Operator for Repeat {
 				break Ok(loop_value);
 			}
 
+			scope.check_interrupts()?;
+
 			// Evaluate expression
 			loop_value = evaluate(&args[1], scope)?;
 
 			iterations += 1.0;
 		}
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("repeat(count, body)\n\nEvaluates `body` `count` times, yielding it's last value.")
+	}
 }
 
 // Sleep for x milliseconds
@@ -114,25 +145,42 @@ pub struct Sleep;
 #[cfg(feature = "std")]
 impl Operator for Sleep {
 	fn evaluate(&self, args: &[expression::Expression], scope: &mut Scope) -> EggResult<Value> {
-		use std::{thread::sleep, time::Duration};
+		use std::{
+			thread::sleep,
+			time::{Duration, Instant},
+		};
 
 		// Assert correct length of arguments
 		debug_assert_eq!(args.len(), 1);
 
-		// Loop
+		// Sleep in short slices, so cancellation and timeouts are observed promptly
 		let sleep_time = evaluate(&args[0], scope)?;
 		if let Value::Number(value) = sleep_time {
-			let duration = Duration::from_millis(value.0 as u64);
-			sleep(duration)
+			let wake_up = Instant::now() + Duration::from_millis(value.0 as u64);
+
+			loop {
+				scope.check_interrupts()?;
+
+				let remaining = wake_up.saturating_duration_since(Instant::now());
+				if remaining.is_zero() {
+					break;
+				}
+
+				sleep(remaining.min(Duration::from_millis(10)));
+			}
 		} else {
 			return Err(EggError::OperatorComplaint("sleep(--) expects a number as it's parameter".to_string()));
 		}
 
 		Ok(sleep_time)
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("sleep(milliseconds)\n\nPauses the script for the given number of milliseconds.")
+	}
 }
 
-// Sleep for ∞ milliseconds
+// Stops the script, the evaluator fills in the location of the call
 pub struct Panic;
 
 impl Operator for Panic {
@@ -140,14 +188,12 @@ impl Operator for Panic {
 		// Assert correct length of arguments
 		debug_assert_eq!(args.len(), 1);
 
-		// Loop
-		match evaluate(&args[0], scope)? {
-			Value::Number(error_code) => {
-				panic!("Program has met an unexpected error: ErrorCode: {error_code}")
-			}
-			Value::String(message) => panic!("{message}"),
-			_ => panic!("Program has terminated prematurely due to an unexpected error"),
-		}
+		let value = evaluate(&args[0], scope)?;
+		Err(EggError::ScriptPanic { value, location: None })
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("panic(message)\n\nStops the script with a message or error code. Unlike other errors, it cannot be caught by `try`.")
 	}
 }
 
@@ -169,4 +215,8 @@ impl Operator for Assert {
 
 		Ok(Value::Nil)
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("assert(condition, message)\n\nFails with `message` if the condition is False.")
+	}
 }
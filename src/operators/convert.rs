@@ -16,8 +16,22 @@ impl Operator for ToString {
 		debug_assert_eq!(args.len(), 1);
 
 		// Evaluate
-		let res = evaluate(&args[0], scope)?;
-		Ok(Value::String(res.to_string().into()))
+		let res = evaluate(&args[0], scope)?.to_string();
+		scope.allocate_string(res.len())?;
+
+		Ok(Value::String(res.into()))
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("str(value)\n\nConverts a value into a String.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(1)
 	}
 }
 
@@ -39,4 +53,16 @@ impl Operator for ToNumber {
 			n => Ok(n),
 		}
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("num(value)\n\nConverts a String or Boolean into a Number.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(1)
+	}
 }
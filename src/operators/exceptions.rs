@@ -0,0 +1,96 @@
+use super::Operator;
+use crate::{
+	error::{EggError, EggResult},
+	evaluator::evaluate,
+	expression::{Expression, Value},
+	scope::Scope,
+};
+use alloc::{format, string::ToString};
+use core::ops::Range;
+
+/// Evaluates it's body, calling a handler with the error if one is raised
+pub struct Try;
+
+impl Operator for Try {
+	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value> {
+		debug_assert_eq!(args.len(), 2);
+
+		// Forget where errors raised before this body came from
+		scope.take_error_origin();
+
+		let error = match evaluate(&args[0], scope) {
+			Err(error) if error.is_catchable() => error,
+			result => return result,
+		};
+		let origin = scope.take_error_origin();
+
+		// The handler is only created once it's needed
+		let Value::Function(handler) = evaluate(&args[1], scope)? else {
+			return Err(EggError::OperatorComplaint("try(--, ...) expects a function as it's handler".to_string()));
+		};
+
+		let error = error_object(&error, origin, scope)?;
+		let span = args[1].span();
+		scope.call_function(handler, &[Expression::Value { value: error, span }])
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some(
+			"try(body, handler)\n\nEvaluates body, yielding it's value. If it raises an error, handler is called with an object describing it instead:\n`kind`, `message`, `location` with the `start` and `end` of the failing expression in the script, and the `value` of errors raised by `throw`.\n\nExiting, cancellation, timeouts and exceeded memory limits cannot be caught.",
+		)
+	}
+}
+
+/// Raises an error carrying any value
+pub struct Throw;
+
+impl Operator for Throw {
+	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value> {
+		debug_assert_eq!(args.len(), 1);
+
+		Err(EggError::Thrown(evaluate(&args[0], scope)?))
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("throw(value)\n\nRaises an error carrying value, which can be caught by `try`.")
+	}
+}
+
+/// Describe an error as an object, for handlers of `try`
+fn error_object(error: &EggError, origin: Option<Range<usize>>, scope: &mut Scope) -> EggResult<Value> {
+	let message = match error {
+		EggError::Thrown(Value::String(message)) => message.to_string(),
+		EggError::Thrown(value) => format!("{value}"),
+		error => error.to_string(),
+	};
+
+	let location = match origin {
+		Some(span) => {
+			let location = scope.create_object()?;
+			let tag = scope.get_object_tag(location.clone())?;
+			scope.allocate_object_entries(2)?;
+
+			scope.extend_object(tag, [("start".into(), Value::from(span.start as f32)), ("end".into(), Value::from(span.end as f32))]);
+			location
+		}
+		None => Value::Nil,
+	};
+
+	let object = scope.create_object()?;
+	let tag = scope.get_object_tag(object.clone())?;
+	scope.allocate_object_entries(4)?;
+	scope.allocate_string(message.len())?;
+
+	let value = if let EggError::Thrown(value) = error { value.clone() } else { Value::Nil };
+	scope.extend_object(
+		tag,
+		[
+			("kind".into(), error.kind().into()),
+			("message".into(), message.as_str().into()),
+			("location".into(), location),
+			("value".into(), value),
+		],
+	);
+
+	Ok(object)
+}
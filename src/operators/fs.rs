@@ -0,0 +1,237 @@
+use super::{Operator, console};
+use crate::{
+	error::{EggError, EggResult, FileSystemError},
+	evaluator::evaluate,
+	expression::{Expression, Value},
+	scope::Scope,
+};
+use alloc::rc::Rc;
+use arcstr::ArcStr;
+use std::{
+	fs, io,
+	path::{Component, Path, PathBuf},
+};
+
+/// The directory scripts may access through the `fs` builtins, and what they may do in it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sandbox {
+	/// Directory all paths are relative to, scripts cannot access anything outside of it
+	pub root: PathBuf,
+	/// Allows `fs.read`, `fs.exists` and `fs.list`
+	pub read: bool,
+	/// Allows `fs.write`, `fs.append` and `fs.remove`
+	pub write: bool,
+}
+
+impl Sandbox {
+	/// A sandbox in `root` which scripts may only read from
+	pub fn read_only(root: impl Into<PathBuf>) -> Sandbox {
+		Sandbox {
+			root: root.into(),
+			read: true,
+			write: false,
+		}
+	}
+
+	/// A sandbox in `root` which scripts may read from and write to
+	pub fn read_write(root: impl Into<PathBuf>) -> Sandbox {
+		Sandbox {
+			root: root.into(),
+			read: true,
+			write: true,
+		}
+	}
+
+	/// Resolve a script provided path inside the root.
+	/// Absolute paths, `..` and symbolic links leading outside of the root are rejected.
+	fn resolve(&self, path: &str) -> Result<PathBuf, FileSystemError> {
+		if !Path::new(path).components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir)) {
+			return Err(FileSystemError::OutsideSandbox);
+		}
+
+		let root = self.root.canonicalize().map_err(io_error)?;
+		let resolved = root.join(path);
+
+		// Files which don't exist yet are checked through their closest existing ancestor
+		let mut existing = resolved.as_path();
+		while fs::symlink_metadata(existing).is_err() {
+			existing = existing.parent().ok_or(FileSystemError::OutsideSandbox)?;
+		}
+
+		match existing.canonicalize() {
+			Ok(canonical) if canonical.starts_with(&root) => Ok(resolved),
+			Ok(_) => Err(FileSystemError::OutsideSandbox),
+			Err(err) => Err(io_error(err)),
+		}
+	}
+
+	/// Resolve a path, checking the sandbox permits the access
+	fn path(&self, path: &str, write: bool) -> EggResult<PathBuf> {
+		let permitted = if write { self.write } else { self.read };
+		let resolved = match permitted {
+			true => self.resolve(path),
+			false if write => Err(FileSystemError::WriteDenied),
+			false => Err(FileSystemError::ReadDenied),
+		};
+
+		resolved.map_err(|reason| EggError::FileSystem { path: path.to_string(), reason })
+	}
+}
+
+/// Evaluate a path argument
+fn path_argument(arg: &Expression, scope: &mut Scope) -> EggResult<ArcStr> {
+	match evaluate(arg, scope)? {
+		Value::String(path) => Ok(path),
+		value => Err(EggError::OperatorComplaint(format!("File paths must be strings, found: {value}"))),
+	}
+}
+
+fn io_error(err: io::Error) -> FileSystemError {
+	match err.kind() {
+		io::ErrorKind::NotFound => FileSystemError::NotFound,
+		_ => FileSystemError::Io(err.to_string()),
+	}
+}
+
+fn failed(path: &str) -> impl FnOnce(io::Error) -> EggError + '_ {
+	move |err| EggError::FileSystem {
+		path: path.to_string(),
+		reason: io_error(err),
+	}
+}
+
+/// Reads a file as a string
+pub struct Read(pub(crate) Rc<Sandbox>);
+
+impl Operator for Read {
+	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value> {
+		debug_assert_eq!(args.len(), 1);
+
+		let path = path_argument(&args[0], scope)?;
+		let contents = fs::read_to_string(self.0.path(&path, false)?).map_err(failed(&path))?;
+		scope.allocate_string(contents.len())?;
+
+		Ok(contents.as_str().into())
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("fs.read(path)\n\nThe contents of a file in the sandbox, as a string.")
+	}
+}
+
+/// Replaces the contents of a file, creating it if needed
+pub struct Write(pub(crate) Rc<Sandbox>);
+
+impl Operator for Write {
+	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value> {
+		let path = path_argument(&args[0], scope)?;
+		let contents = console::display(&args[1..], "", scope)?;
+		fs::write(self.0.path(&path, true)?, contents).map_err(failed(&path))?;
+
+		Ok(Value::Nil)
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("fs.write(path, values...)\n\nWrites the values to a file in the sandbox as `print` would, replacing it's contents.")
+	}
+}
+
+/// Adds to the end of a file, creating it if needed
+pub struct Append(pub(crate) Rc<Sandbox>);
+
+impl Operator for Append {
+	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value> {
+		use io::Write;
+
+		let path = path_argument(&args[0], scope)?;
+		let contents = console::display(&args[1..], "", scope)?;
+		fs::OpenOptions::new()
+			.append(true)
+			.create(true)
+			.open(self.0.path(&path, true)?)
+			.and_then(|mut file| file.write_all(contents.as_bytes()))
+			.map_err(failed(&path))?;
+
+		Ok(Value::Nil)
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("fs.append(path, values...)\n\nWrites the values to the end of a file in the sandbox as `print` would.")
+	}
+}
+
+/// Checks if a file or directory exists
+pub struct Exists(pub(crate) Rc<Sandbox>);
+
+impl Operator for Exists {
+	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value> {
+		debug_assert_eq!(args.len(), 1);
+
+		let path = path_argument(&args[0], scope)?;
+		Ok(self.0.path(&path, false)?.exists().into())
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("fs.exists(path)\n\nWhether a file or directory exists in the sandbox.")
+	}
+}
+
+/// Lists the entries of a directory
+pub struct List(pub(crate) Rc<Sandbox>);
+
+impl Operator for List {
+	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value> {
+		let path = match args.first() {
+			Some(arg) => path_argument(arg, scope)?,
+			None => arcstr::literal!("."),
+		};
+
+		let mut names = fs::read_dir(self.0.path(&path, false)?)
+			.and_then(|entries| entries.map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned())).collect::<io::Result<Vec<_>>>())
+			.map_err(failed(&path))?;
+		names.sort();
+
+		let object = scope.create_object()?;
+		let tag = scope.get_object_tag(object.clone())?;
+		scope.allocate_object_entries(names.len())?;
+		scope.allocate_string(names.iter().map(String::len).sum())?;
+
+		scope.extend_object(tag, names.into_iter().enumerate().map(|(idx, name)| (Value::from(idx as f32), name.as_str().into())));
+
+		Ok(object)
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("fs.list(path?)\n\nThe sorted names of the entries in a directory of the sandbox, by default it's root, as an object keyed by position from 0.")
+	}
+}
+
+/// Deletes a file or an empty directory
+pub struct Remove(pub(crate) Rc<Sandbox>);
+
+impl Operator for Remove {
+	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value> {
+		debug_assert_eq!(args.len(), 1);
+
+		let path = path_argument(&args[0], scope)?;
+		let resolved = self.0.path(&path, true)?;
+		if Path::new(path.as_str()).components().all(|component| component == Component::CurDir) {
+			return Err(EggError::FileSystem {
+				path: path.to_string(),
+				reason: FileSystemError::Io("the sandbox root cannot be removed".into()),
+			});
+		}
+
+		match fs::symlink_metadata(&resolved) {
+			Ok(metadata) if metadata.is_dir() => fs::remove_dir(resolved),
+			_ => fs::remove_file(resolved),
+		}
+		.map_err(failed(&path))?;
+
+		Ok(Value::Nil)
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("fs.remove(path)\n\nDeletes a file or an empty directory in the sandbox.")
+	}
+}
@@ -5,15 +5,22 @@ use crate::{
 };
 use alloc::{boxed::Box, collections::BTreeMap};
 
+#[cfg(feature = "std")]
+pub use fs::Sandbox;
+
 // egg-std definitions
 mod arithmetic;
 mod boolean;
 mod comparison;
 mod control_flow;
 mod convert;
+mod exceptions;
 
+pub(crate) mod console;
+#[cfg(feature = "std")]
+mod fs;
 #[cfg(feature = "std")]
-mod console;
+mod process;
 
 mod stringtools;
 mod variables;
@@ -30,6 +37,27 @@ pub trait Operator {
 	/// `[operators]` is a map of all other operators; Can be used directly, but it's main use is to invoke [`evaluate`](crate::evaluator::evaluate) on arguments.
 	///
 	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value>;
+
+	/// Documentation for this Operator, displayed by editor tooling.
+	///
+	/// By convention the first line is a signature, eg: `sum(numbers...)`, followed by a blank line and a description.
+	fn documentation(&self) -> Option<&'static str> {
+		None
+	}
+
+	/// Whether this Operator has no side effects besides evaluating it's arguments, and always yields the same result for the same arguments.
+	///
+	/// Pure operators called with only literal arguments are evaluated ahead of time by the [`optimizer`](crate::optimizer).
+	fn is_pure(&self) -> bool {
+		false
+	}
+
+	/// The number of arguments this Operator expects, or `None` if it accepts any number.
+	///
+	/// Calls with a different number of arguments are never evaluated ahead of time.
+	fn arity(&self) -> Option<usize> {
+		None
+	}
 }
 
 /// Create an empty map of operations
@@ -54,6 +82,10 @@ pub fn minimal<'a>(map: &'a mut BTreeMap<&'static str, Box<dyn Operator>>) -> &'
 	map.insert("panic", Box::new(control_flow::Panic));
 	map.insert("assert", Box::new(control_flow::Assert));
 
+	// Error handling
+	map.insert("try", Box::new(exceptions::Try));
+	map.insert("throw", Box::new(exceptions::Throw));
+
 	// Comparison
 	map.insert("equals", Box::new(comparison::Equals));
 	map.insert("not_equals", Box::new(comparison::NotEquals));
@@ -104,23 +136,86 @@ pub fn strings(map: &mut BTreeMap<&'static str, Box<dyn Operator>>) {
 	map.insert("string.trim", Box::new(stringtools::Trim));
 }
 
-/// Console Functions
-#[cfg(feature = "std")]
-pub fn console<'a>(map: &'a mut BTreeMap<&'static str, Box<dyn Operator>>) {
+/// Console Functions, writing to and reading from the [`Output`](crate::io::Output) and [`Input`](crate::io::Input) installed on the scope
+pub fn console(map: &mut BTreeMap<&'static str, Box<dyn Operator>>) {
 	map.insert("print", Box::new(console::Print));
 	map.insert("println", Box::new(console::PrintLine));
 	map.insert("readline", Box::new(console::ReadLine));
 }
 
+/// Functions interacting with the host system
+#[cfg(feature = "std")]
+pub fn system(map: &mut BTreeMap<&'static str, Box<dyn Operator>>) {
+	map.insert("sleep", Box::new(control_flow::Sleep));
+}
+
+/// Arguments, environment variables and exiting with a status.
+///
+/// `exit(code)` stops the script by raising [`EggError::Exit`](crate::error::EggError::Exit) through [`evaluate`](crate::evaluator::evaluate),
+/// rather than exiting the process, so hosts decide how to handle it. Not included in [`full`], as `args` are provided by the host.
+///
+/// ```
+/// use egglang::prelude::*;
+///
+/// let mut operators = operators::empty();
+/// operators::full(&mut operators);
+/// operators::process(&mut operators, ["--verbose"]);
+///
+/// let script = parse("if(equals(object.get(args(), 0), \"--verbose\"), exit(3), exit())", &operators).unwrap();
+/// let result = evaluate(&script[0], &mut Scope::default());
+/// assert!(matches!(result, Err(EggError::Exit(3))));
+/// ```
+#[cfg(feature = "std")]
+pub fn process<A: Into<arcstr::ArcStr>>(map: &mut BTreeMap<&'static str, Box<dyn Operator>>, args: impl IntoIterator<Item = A>) {
+	map.insert("args", Box::new(process::Args(args.into_iter().map(Into::into).collect())));
+	map.insert("env.get", Box::new(process::EnvGet));
+	map.insert("exit", Box::new(process::Exit));
+}
+
+/// Reading and writing files, confined to the [`Sandbox`] configured by the host.
+///
+/// Paths are relative to the sandbox's root, absolute paths, `..` and symbolic links leading out of it are refused.
+/// Failures are reported as [`EggError::FileSystem`](crate::error::EggError::FileSystem). Not included in [`full`].
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::error::FileSystemError;
+///
+/// let root = std::env::temp_dir().join("egg-fs-doctest");
+/// std::fs::create_dir_all(&root).unwrap();
+///
+/// let mut operators = operators::empty();
+/// operators::full(&mut operators);
+/// operators::fs(&mut operators, operators::Sandbox::read_only(&root));
+///
+/// let script = parse("fs.exists(\"missing.txt\")\nfs.read(\"../secret.txt\")\nfs.write(\"out.txt\", 1)", &operators).unwrap();
+/// let mut scope = Scope::default();
+///
+/// assert_eq!(evaluate(&script[0], &mut scope).unwrap(), Value::Boolean(false));
+/// let Err(EggError::FileSystem { reason, .. }) = evaluate(&script[1], &mut scope) else { panic!() };
+/// assert_eq!(reason, FileSystemError::OutsideSandbox);
+/// let Err(EggError::FileSystem { reason, .. }) = evaluate(&script[2], &mut scope) else { panic!() };
+/// assert_eq!(reason, FileSystemError::WriteDenied);
+/// ```
+#[cfg(feature = "std")]
+pub fn fs(map: &mut BTreeMap<&'static str, Box<dyn Operator>>, sandbox: Sandbox) {
+	let sandbox = alloc::rc::Rc::new(sandbox);
+
+	map.insert("fs.read", Box::new(fs::Read(sandbox.clone())));
+	map.insert("fs.write", Box::new(fs::Write(sandbox.clone())));
+	map.insert("fs.append", Box::new(fs::Append(sandbox.clone())));
+	map.insert("fs.exists", Box::new(fs::Exists(sandbox.clone())));
+	map.insert("fs.list", Box::new(fs::List(sandbox.clone())));
+	map.insert("fs.remove", Box::new(fs::Remove(sandbox)));
+}
+
 /// All Internal functions defined in `Egg`
 pub fn full(map: &mut BTreeMap<&'static str, Box<dyn Operator>>) {
 	minimal(map);
 	objects(map);
 	strings(map);
+	console(map);
 
 	#[cfg(feature = "std")]
-	{
-		map.insert("sleep", Box::new(control_flow::Sleep));
-		console(map);
-	}
+	system(map);
 }
@@ -0,0 +1,71 @@
+use super::Operator;
+use crate::{
+	error::{EggError, EggResult},
+	evaluator::evaluate,
+	expression::{Expression, Value},
+	scope::Scope,
+};
+use alloc::vec::Vec;
+use arcstr::ArcStr;
+
+/// The arguments passed to the script by the host
+pub struct Args(pub(crate) Vec<ArcStr>);
+
+impl Operator for Args {
+	fn evaluate(&self, _: &[Expression], scope: &mut Scope) -> EggResult<Value> {
+		let object = scope.create_object()?;
+		let tag = scope.get_object_tag(object.clone())?;
+		scope.allocate_object_entries(self.0.len())?;
+
+		scope.extend_object(tag, self.0.iter().enumerate().map(|(idx, arg)| (Value::from(idx as f32), Value::String(arg.clone()))));
+
+		Ok(object)
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("args()\n\nThe arguments passed to the script, as an object of strings keyed by their position from 0.")
+	}
+}
+
+/// Reads an environment variable
+pub struct EnvGet;
+
+impl Operator for EnvGet {
+	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value> {
+		debug_assert_eq!(args.len(), 1);
+
+		match evaluate(&args[0], scope)? {
+			Value::String(name) => match std::env::var(name.as_str()) {
+				Ok(value) => {
+					scope.allocate_string(value.len())?;
+					Ok(value.as_str().into())
+				}
+				Err(_) => Ok(Value::Nil),
+			},
+			value => Err(EggError::OperatorComplaint(format!("Environment variable names must be strings, found: {value}"))),
+		}
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("env.get(name)\n\nThe value of an environment variable, or Nil if it isn't set.")
+	}
+}
+
+/// Stops the script with an exit code, by raising [`EggError::Exit`]
+pub struct Exit;
+
+impl Operator for Exit {
+	fn evaluate(&self, args: &[Expression], scope: &mut Scope) -> EggResult<Value> {
+		let code = match args.first().map(|arg| evaluate(arg, scope)).transpose()? {
+			None => 0,
+			Some(Value::Number(code)) => code.0 as i32,
+			Some(value) => return Err(EggError::OperatorComplaint(format!("Exit codes must be numbers, found: {value}"))),
+		};
+
+		Err(EggError::Exit(code))
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("exit(code?)\n\nStops the script with an exit code, 0 by default.")
+	}
+}
@@ -5,7 +5,11 @@ use crate::{
 	expression::{self, Value},
 	scope::Scope,
 };
-use alloc::string::{String, ToString};
+use alloc::{
+	format,
+	string::{String, ToString},
+};
+use arcstr::ArcStr;
 
 pub struct Concat;
 
@@ -20,8 +24,17 @@ impl Operator for Concat {
 			}
 		}
 
+		scope.allocate_string(result.len())?;
 		Ok(Value::String(result.into()))
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("string.concat(strings...)\n\nJoins strings together.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
 }
 
 pub struct Length;
@@ -40,6 +53,18 @@ impl Operator for Length {
 
 		Ok(Value::Number((value as f32).into()))
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("string.length(string)\n\nThe length of a string in bytes.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(1)
+	}
 }
 
 /// Builtin for slicing and indexing into strings
@@ -70,10 +95,25 @@ impl Operator for Slice {
 		};
 
 		let start = start.0 as usize;
-		let result = &base[start..start + length];
+		let Some(result) = start.checked_add(length).and_then(|end| base.get(start..end)) else {
+			return Err(EggError::OperatorComplaint(format!("Cannot slice {length} bytes from {start} of a string of length {}", base.len())));
+		};
+		scope.allocate_string(result.len())?;
 
 		Ok(Value::String(result.into()))
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("string.slice(string, start, length)\n\nA substring of `length` bytes, from `start`. Negative starts count from the end.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(3)
+	}
 }
 
 /// Converts a String to UpperCase
@@ -91,8 +131,21 @@ impl Operator for ToUpper {
 			_ => return Err(EggError::OperatorComplaint("Cannot convert non-string to uppercase".to_string())),
 		};
 
+		scope.allocate_string(value.len())?;
 		Ok(Value::String(value.into()))
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("string.to_upper(string)\n\nConverts a string to upper case.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(1)
+	}
 }
 
 /// Converts a String to Upper case
@@ -110,8 +163,21 @@ impl Operator for ToLower {
 			_ => return Err(EggError::OperatorComplaint("Cannot convert non-string to lowercase".to_string())),
 		};
 
+		scope.allocate_string(value.len())?;
 		Ok(Value::String(value.into()))
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("string.to_lower(string)\n\nConverts a string to lower case.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(1)
+	}
 }
 
 /// Trims whitespace from the start and end of a string
@@ -124,9 +190,24 @@ impl Operator for Trim {
 
 		// Evaluate
 		let res = evaluate(&args[0], scope)?;
-		match res {
-			Value::String(string) => Ok(Value::String(string.trim().into())),
-			_ => Err(EggError::OperatorComplaint("Cannot trim non-string".to_string())),
-		}
+		let value: ArcStr = match res {
+			Value::String(string) => string.trim().into(),
+			_ => return Err(EggError::OperatorComplaint("Cannot trim non-string".to_string())),
+		};
+
+		scope.allocate_string(value.len())?;
+		Ok(Value::String(value))
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("string.trim(string)\n\nRemoves whitespace from the start and end of a string.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(1)
 	}
 }
@@ -16,7 +16,7 @@ impl Operator for Define {
 		let name = &args[0];
 
 		match name {
-			expression::Expression::Word { name } | expression::Expression::Value { value: Value::String(name) } => {
+			expression::Expression::Word { name, .. } | expression::Expression::Value { value: Value::String(name), .. } => {
 				let value = evaluate(&args[1], scope)?;
 				scope.insert(name.clone(), value)?;
 				Ok(Value::Nil)
@@ -24,6 +24,10 @@ impl Operator for Define {
 			_ => Err(EggError::OperatorComplaint("Numbers and Nil cannot be used as variable names".to_string())),
 		}
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("define(name, value)\n\nDefines a new variable in the current scope.")
+	}
 }
 
 /// Mutates an existing variable
@@ -35,17 +39,21 @@ impl Operator for Set {
 		let variable_name = &args[0];
 
 		match variable_name {
-			expression::Expression::Word { name } => {
+			expression::Expression::Word { name, .. } => {
 				let new_value = evaluate(&args[1], scope)?;
 				scope.update(name.clone(), new_value)
 			}
 			v => {
-				return Err(EggError::OperatorComplaint(format!("Non-word variable name. Got: {:?}", v)));
+				return Err(EggError::OperatorComplaint(format!("Non-word variable name. Got: {v}")));
 			}
 		}
 
 		Ok(Value::Nil)
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("set(name, value)\n\nUpdates an existing variable.")
+	}
 }
 
 /// Deletes an existing variable
@@ -58,16 +66,20 @@ impl Operator for Delete {
 		let name = &args[0];
 
 		let res = match name {
-			expression::Expression::Word { name } => scope.delete(name.as_str()),
-			expression::Expression::Value { value } => match value {
+			expression::Expression::Word { name, .. } => scope.delete(name.as_str()),
+			expression::Expression::Value { value, .. } => match value {
 				Value::String(name) => scope.delete(name.as_str()),
 				val => return Err(EggError::OperatorComplaint(format!("Cannot delete {val}"))),
 			},
-			v => return Err(EggError::OperatorComplaint(format!("Cannot delete {v:?}"))),
+			v => return Err(EggError::OperatorComplaint(format!("Cannot delete {v}"))),
 		};
 
 		Ok(res.unwrap_or(Value::Nil))
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("delete(name)\n\nDeletes a variable, yielding it's value.")
+	}
 }
 
 /// Checks if a variable exists
@@ -79,8 +91,8 @@ impl Operator for Exists {
 		let name = &args[0];
 
 		let res = match name {
-			expression::Expression::Word { name } => scope.exists(name.as_str()),
-			expression::Expression::Value { value } => match value {
+			expression::Expression::Word { name, .. } => scope.exists(name.as_str()),
+			expression::Expression::Value { value, .. } => match value {
 				Value::String(name) => scope.exists(name.as_str()),
 				val => return Err(EggError::OperatorComplaint(format!("Cannot check if {val} exists"))),
 			},
@@ -91,6 +103,10 @@ impl Operator for Exists {
 
 		Ok(res.into())
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("exists(name)\n\nTrue if a variable is defined in the current scope or it's parents.")
+	}
 }
 
 // Returns the value's type
@@ -111,4 +127,16 @@ impl super::Operator for TypeOf {
 			Value::Object(_) => Value::String(arcstr::literal!("__TYPE__OBJECT")),
 		})
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("typeof(value)\n\nThe type of a value, compare against Number, String, Boolean, Function, Object or Nil.")
+	}
+
+	fn is_pure(&self) -> bool {
+		true
+	}
+
+	fn arity(&self) -> Option<usize> {
+		Some(1)
+	}
 }
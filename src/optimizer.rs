@@ -0,0 +1,120 @@
+use alloc::vec::Vec;
+use core::mem;
+
+use crate::{
+	expression::{Expression, Function, Value, VisitorMut, walk_expression_mut},
+	scope::Scope,
+};
+
+/// Optimizes a parsed script, producing expressions that evaluate to the same results with less work:
+///
+/// - Calls to [pure](crate::operators::Operator::is_pure) builtins with only literal arguments are evaluated ahead of time, eg: `multiply(12, 12)` becomes `144`.
+///   Calls that would fail, including those with the wrong number of arguments, are kept so the error is still raised at runtime.
+/// - `if` with a constant condition is replaced by the branch it would take.
+/// - Nested `do` blocks are flattened into their parent, literals with no effect are removed, and `do` with a single expression is replaced by it.
+///
+/// Builtins are recognized by the name they were parsed with, so this assumes `do` and `if` are the standard builtins.
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::optimizer::optimize;
+///
+/// let mut operators = operators::empty();
+/// operators::minimal(&mut operators);
+///
+/// let script = parse("repeat(3, do(5, do(set(x, sum(x, multiply(2, 5)))))) if(greater_than(2, 1), x, 0)", &operators).unwrap();
+/// let optimized = optimize(script);
+///
+/// assert_eq!(optimized[0].to_string(), "repeat(3, set(x, sum(x, 10)))");
+/// assert_eq!(optimized[1].to_string(), "x");
+/// ```
+pub fn optimize(mut expressions: Vec<Expression>) -> Vec<Expression> {
+	// Pure operators don't touch the scope, besides evaluating their literal arguments
+	let mut optimizer = Optimizer { scope: Scope::default() };
+	optimizer.visit_expressions_mut(&mut expressions);
+	expressions
+}
+
+struct Optimizer {
+	scope: Scope,
+}
+
+impl VisitorMut for Optimizer {
+	fn visit_expression_mut(&mut self, expr: &mut Expression) {
+		// Simplify bottom-up, so calls see their parameters already folded
+		walk_expression_mut(self, expr);
+
+		let placeholder = Expression::Value { value: Value::Nil, span: expr.span() };
+		*expr = simplify(mem::replace(expr, placeholder), &mut self.scope);
+	}
+}
+
+fn simplify(expr: Expression, scope: &mut Scope) -> Expression {
+	let Expression::FnCall { function, mut parameters, span } = expr else { return expr };
+
+	let Function::Host(name, op) = &function else {
+		return Expression::FnCall { function, parameters, span };
+	};
+
+	match (name.as_str(), parameters.as_mut_slice()) {
+		("if", [Expression::Value { value, .. }, _, _]) => {
+			let condition = match value {
+				Value::Number(n) => Some(n.0 != 0.0),
+				Value::Boolean(b) => Some(*b),
+				// Raises an error at runtime
+				_ => None,
+			};
+
+			if let Some(condition) = condition {
+				return parameters.swap_remove(if condition { 1 } else { 2 });
+			}
+		}
+		("do", _) => {
+			parameters = flatten(parameters);
+
+			if parameters.len() == 1 {
+				return parameters.remove(0);
+			}
+		}
+		_ => {}
+	}
+
+	let op = unsafe { op.as_ref().unwrap_unchecked() };
+	if op.is_pure()
+		&& op.arity().is_none_or(|arity| arity == parameters.len())
+		&& parameters.iter().all(|parameter| matches!(parameter, Expression::Value { .. }))
+		&& let Ok(value) = op.evaluate(&parameters, scope)
+		&& value.is_primitive()
+	{
+		return Expression::Value { value, span };
+	}
+
+	Expression::FnCall { function, parameters, span }
+}
+
+/// Inline the expressions of nested `do` blocks, dropping literals whose value is discarded
+fn flatten(expressions: Vec<Expression>) -> Vec<Expression> {
+	let count = expressions.len();
+	let mut flattened = Vec::with_capacity(count);
+
+	for (idx, expr) in expressions.into_iter().enumerate() {
+		match expr {
+			// An empty block yields Nil, which matters if it's the last expression
+			Expression::FnCall {
+				function: Function::Host(name, _),
+				parameters,
+				..
+			} if name == "do" && (!parameters.is_empty() || idx + 1 < count) => flattened.extend(parameters),
+			expr => flattened.push(expr),
+		}
+	}
+
+	let last = flattened.len().saturating_sub(1);
+	let mut idx = 0;
+	flattened.retain(|expr| {
+		idx += 1;
+		idx - 1 == last || !matches!(expr, Expression::Value { .. })
+	});
+
+	flattened
+}
@@ -1,4 +1,5 @@
 use alloc::{boxed::Box, collections::btree_map::BTreeMap, string::ToString, vec::Vec};
+use arcstr::ArcStr;
 use core::ops::Range;
 use logos::Logos;
 
@@ -8,14 +9,21 @@ use crate::{
 	operators::Operator,
 };
 
-#[derive(logos::Logos, Debug, PartialEq)]
-enum Token {
+/// Tokens of an Egg script, see [`syntax`](crate::syntax) for a lossless tree of tokens.
+///
+/// Separators, whitespace and comments are trivia, which is ignored by [`parse`].
+#[derive(logos::Logos, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+	/// A string literal, including it's quotes
 	#[regex("\"([^\"\n])*\"")]
 	String,
+	/// A number literal
 	#[regex(r"-?\d+(\.\d+)?([eE]-?\d+)?")]
 	Float,
+	/// A variable or function name
 	#[regex(r#"[\p{L}_][\p{L}\d_.]*"#)]
 	Word,
+	/// `True` or `False`
 	#[regex("True|False")]
 	Boolean,
 
@@ -24,14 +32,23 @@ enum Token {
 	#[regex(r"\)")]
 	RightBracket,
 
-	#[regex(",", logos::skip)]
+	/// Separates arguments, optional as whitespace does the same
+	#[regex(",")]
 	Comma,
-	#[regex(r"\s+", logos::skip)]
+	#[regex(r"\s+")]
 	WhiteSpace,
-	#[regex(r"#.*(\s)+", logos::skip)]
+	/// A comment running to the end of the line, starting with `#`
+	#[regex(r"#[^\n]*")]
 	Comment,
 }
 
+impl Token {
+	/// Whether this token is insignificant to the meaning of a script
+	pub fn is_trivia(&self) -> bool {
+		matches!(self, Token::Comma | Token::WhiteSpace | Token::Comment)
+	}
+}
+
 /// Given a valid script: `S`, parses and returns a vector of [`Expressions`](Expression).
 /// Iterate over result and execute each expression using [`evaluate`](crate::evaluator::evaluate).
 ///
@@ -46,32 +63,24 @@ pub fn parse<S: AsRef<str>>(script: S, operators: &BTreeMap<&str, Box<dyn Operat
 	let mut stack = Vec::with_capacity(16);
 
 	for (token, span) in lex.spanned() {
-		let token = token.map_err(|_| EggError::UnknownToken(script[span.clone()].to_string()))?;
+		let token = token.map_err(|_| EggError::UnknownToken(script[span.clone()].to_string(), span.clone()))?;
 		parse_token(&token, script, span, &mut exprs, &mut stack, operators)?;
 	}
 
+	// Brackets left open at the end of the script
+	if let Some((_, offset)) = stack.first() {
+		return Err(EggError::UnbalancedBrackets(*offset));
+	}
+
 	exprs.shrink_to_fit();
 	Ok(exprs)
 }
 
-fn parse_token(token: &Token, source: &str, span: Range<usize>, exprs: &mut Vec<Expression>, stack: &mut Vec<usize>, operators: &BTreeMap<&str, Box<dyn Operator>>) -> EggResult<()> {
-	let data = &source[span.clone()];
-
+fn parse_token(token: &Token, source: &str, span: Range<usize>, exprs: &mut Vec<Expression>, stack: &mut Vec<(usize, usize)>, operators: &BTreeMap<&str, Box<dyn Operator>>) -> EggResult<()> {
 	match token {
-		Token::Boolean => exprs.push(Expression::Value {
-			value: Value::Boolean(data == "True"),
-		}),
-		Token::String => exprs.push(Expression::Value {
-			value: Value::String(data[1..data.len() - 1].into()),
-		}),
-		Token::Float => exprs.push(Expression::Value {
-			value: Value::Number(data.parse().unwrap()),
-		}),
-		Token::Word => exprs.push(Expression::Word { name: data.into() }),
-
-		Token::LeftBracket => stack.push(exprs.len()),
+		Token::LeftBracket => stack.push((exprs.len(), span.start)),
 		Token::RightBracket => {
-			let start = stack.pop().ok_or(EggError::UnbalancedBrackets(span.start))?;
+			let (start, _) = stack.pop().ok_or(EggError::UnbalancedBrackets(span.start))?;
 			let end = exprs.len();
 
 			// Collect operation arguments
@@ -79,23 +88,99 @@ fn parse_token(token: &Token, source: &str, span: Range<usize>, exprs: &mut Vec<
 
 			// Get name of operation
 			let name = exprs.pop().ok_or(EggError::UnbalancedBrackets(span.start))?;
-			let operation = Expression::FnCall {
-				function: match name {
-					Expression::Word { name } => match operators.get(name.as_str()) {
-						Some(op) => Function::Host(op.as_ref() as _),
-						None => Function::Script(name.clone()),
-					},
-					_ => return Err(EggError::ParserError(span, "Cannot use non-word as operation name".into())),
-				},
-				parameters,
+			let operation = match name {
+				Expression::Word { name, span: name_span } => call(name, parameters, name_span.start..span.end, operators),
+				_ => return Err(EggError::ParserError(span, "Cannot use non-word as operation name".into())),
 			};
 
 			// Push operation to stack
 			exprs.push(operation);
 		}
 
-		_ => unreachable!("Other tokens are automatically filtered out by logos"),
+		token if token.is_trivia() => {}
+		token => exprs.push(atom(token, &source[span.clone()], span)),
 	};
 
 	Ok(())
 }
+
+/// The [`Expression`] for a literal or word token
+pub(crate) fn atom(token: &Token, data: &str, span: Range<usize>) -> Expression {
+	match token {
+		Token::Boolean => Expression::Value {
+			value: Value::Boolean(data == "True"),
+			span,
+		},
+		Token::String => Expression::Value {
+			value: Value::String(data[1..data.len() - 1].into()),
+			span,
+		},
+		Token::Float => Expression::Value {
+			value: Value::Number(data.parse().unwrap()),
+			span,
+		},
+		_ => Expression::Word { name: data.into(), span },
+	}
+}
+
+/// The [`Expression`] calling function `name`, resolving builtins from `operators`
+pub(crate) fn call(name: ArcStr, parameters: Vec<Expression>, span: Range<usize>, operators: &BTreeMap<&str, Box<dyn Operator>>) -> Expression {
+	Expression::FnCall {
+		function: match operators.get(name.as_str()) {
+			Some(op) => Function::Host(name.clone(), op.as_ref() as _),
+			None => Function::Script(name),
+		},
+		parameters,
+		span,
+	}
+}
+
+/// Maps byte offsets in a script to 1-based line and column numbers, and back.
+///
+/// ```
+/// use egglang::parser::LineIndex;
+///
+/// let index = LineIndex::new("define(x, 5)\nprintln(x)");
+/// assert_eq!(index.location(13), (2, 1));
+/// assert_eq!(index.offset(2, 9), Some(21));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+	line_starts: Vec<usize>,
+	length: usize,
+}
+
+impl LineIndex {
+	/// Index the line breaks in `source`
+	pub fn new(source: &str) -> LineIndex {
+		let line_starts = core::iter::once(0).chain(source.match_indices('\n').map(|(idx, _)| idx + 1)).collect();
+		LineIndex { line_starts, length: source.len() }
+	}
+
+	/// Number of lines in the source
+	pub fn lines(&self) -> usize {
+		self.line_starts.len()
+	}
+
+	/// The `(line, column)` of a byte offset, both 1-based. Columns are counted in bytes.
+	pub fn location(&self, offset: usize) -> (usize, usize) {
+		let line = self.line_starts.partition_point(|&start| start <= offset).max(1);
+		(line, offset - self.line_starts[line - 1] + 1)
+	}
+
+	/// The byte offset of a 1-based `(line, column)`, if it lies within the source
+	pub fn offset(&self, line: usize, column: usize) -> Option<usize> {
+		let start = *self.line_starts.get(line.checked_sub(1)?)?;
+		let offset = start + column.checked_sub(1)?;
+
+		(offset <= self.length).then_some(offset)
+	}
+
+	/// Byte range of a 1-based line, excluding the line break
+	pub fn line_span(&self, line: usize) -> Option<Range<usize>> {
+		let start = *self.line_starts.get(line.checked_sub(1)?)?;
+		let end = self.line_starts.get(line).map(|next| next - 1).unwrap_or(self.length);
+
+		Some(start..end)
+	}
+}
@@ -0,0 +1,356 @@
+use alloc::{boxed::Box, collections::btree_map::BTreeMap, format, string::String, vec::Vec};
+use arcstr::ArcStr;
+use core::ops::Range;
+
+use crate::{
+	error::{EggError, EggResult},
+	expression::{Expression, Function, Value},
+	operators::Operator,
+};
+
+/// First bytes of every precompiled script
+pub const MAGIC: [u8; 4] = *b"EGG\0";
+
+/// Version of the format, scripts precompiled with a different version are rejected on load
+pub const VERSION: u16 = 1;
+
+/// Deepest nesting of calls accepted on load, deeper scripts are rejected rather than overflowing the stack
+pub const MAX_DEPTH: usize = 512;
+
+// Tags preceding each expression
+const NIL: u8 = 0;
+const NUMBER: u8 = 1;
+const BOOLEAN: u8 = 2;
+const STRING: u8 = 3;
+const WORD: u8 = 4;
+const HOST_CALL: u8 = 5;
+const SCRIPT_CALL: u8 = 6;
+
+/// Whether `bytes` start with the [`MAGIC`] of a precompiled script
+pub fn is_precompiled(bytes: &[u8]) -> bool {
+	bytes.starts_with(&MAGIC)
+}
+
+/// Serializes a parsed script into a compact binary format, starting with [`MAGIC`] and [`VERSION`].
+///
+/// Builtins are stored by the name they were called with, and relinked by [`from_bytes`].
+/// Only literals can be stored, so references to functions or objects are an error.
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::precompiled;
+///
+/// let mut operators = operators::empty();
+/// operators::minimal(&mut operators);
+///
+/// let script = parse("define(x, sum(1, 2)) greet(x, \"Egg\")", &operators).unwrap();
+/// let bytes = precompiled::to_bytes(&script).unwrap();
+/// assert!(precompiled::is_precompiled(&bytes));
+///
+/// // Load the script again, skipping the parser
+/// let loaded = precompiled::from_bytes(&bytes, &operators).unwrap();
+/// assert_eq!(format!("{loaded:?}"), format!("{script:?}"));
+/// ```
+pub fn to_bytes(expressions: &[Expression]) -> EggResult<Vec<u8>> {
+	let mut out = Vec::with_capacity(expressions.len() * 16);
+	out.extend_from_slice(&MAGIC);
+	out.extend_from_slice(&VERSION.to_le_bytes());
+
+	write_usize(&mut out, expressions.len());
+	expressions.iter().try_for_each(|expr| write_expression(&mut out, expr))?;
+
+	Ok(out)
+}
+
+/// Loads a script serialized by [`to_bytes`], relinking builtins against `operators`.
+///
+/// Fails if the header or data is malformed, calls are nested deeper than [`MAX_DEPTH`], or if the script calls a builtin missing from `operators`.
+///
+/// > IMPORTANT: As with [`parse`](crate::parser::parse), mutating `operators` while the loaded expressions are alive is undefined behaviour
+pub fn from_bytes(bytes: &[u8], operators: &BTreeMap<&str, Box<dyn Operator>>) -> EggResult<Vec<Expression>> {
+	let mut reader = Reader { bytes, offset: 0 };
+
+	if reader.take(MAGIC.len())? != MAGIC {
+		return Err(invalid("Missing header, not a precompiled script"));
+	}
+
+	let version = u16::from_le_bytes(reader.array()?);
+	if version != VERSION {
+		return Err(invalid(format!("Unsupported version {version}, expected {VERSION}")));
+	}
+
+	let count = reader.usize()?;
+	let mut expressions = Vec::with_capacity(count.min(bytes.len()));
+	for _ in 0..count {
+		expressions.push(reader.expression(operators, 0)?);
+	}
+
+	if reader.offset != bytes.len() {
+		return Err(invalid(format!("Unexpected data after the last expression at byte {}", reader.offset)));
+	}
+
+	Ok(expressions)
+}
+
+fn invalid(reason: impl Into<String>) -> EggError {
+	EggError::InvalidPrecompiledScript(reason.into())
+}
+
+fn write_usize(out: &mut Vec<u8>, mut value: usize) {
+	// LEB128, 7 bits at a time
+	while value >= 0x80 {
+		out.push(value as u8 | 0x80);
+		value >>= 7;
+	}
+	out.push(value as u8);
+}
+
+fn write_str(out: &mut Vec<u8>, value: &str) {
+	write_usize(out, value.len());
+	out.extend_from_slice(value.as_bytes());
+}
+
+fn write_expression(out: &mut Vec<u8>, expr: &Expression) -> EggResult<()> {
+	match expr {
+		Expression::Value { value, .. } => match value {
+			Value::Nil => out.push(NIL),
+			Value::Number(n) => {
+				out.push(NUMBER);
+				out.extend_from_slice(&n.0.to_le_bytes());
+			}
+			Value::Boolean(b) => out.extend_from_slice(&[BOOLEAN, *b as u8]),
+			Value::String(s) => {
+				out.push(STRING);
+				write_str(out, s);
+			}
+			value => return Err(invalid(format!("Cannot precompile a reference to a {value}"))),
+		},
+		Expression::Word { name, .. } => {
+			out.push(WORD);
+			write_str(out, name);
+		}
+		Expression::FnCall { function, parameters, .. } => {
+			let name = match function {
+				Function::Host(name, _) => {
+					out.push(HOST_CALL);
+					name
+				}
+				Function::Script(name) => {
+					out.push(SCRIPT_CALL);
+					name
+				}
+			};
+
+			write_str(out, name);
+			write_usize(out, parameters.len());
+			parameters.iter().try_for_each(|parameter| write_expression(out, parameter))?;
+		}
+	}
+
+	let span = expr.span();
+	write_usize(out, span.start);
+	write_usize(out, span.end);
+	Ok(())
+}
+
+struct Reader<'a> {
+	bytes: &'a [u8],
+	offset: usize,
+}
+
+impl Reader<'_> {
+	fn take(&mut self, length: usize) -> EggResult<&[u8]> {
+		let end = self.offset.checked_add(length).filter(|end| *end <= self.bytes.len());
+		let end = end.ok_or_else(|| invalid(format!("Unexpected end of data at byte {}", self.offset)))?;
+
+		let bytes = &self.bytes[self.offset..end];
+		self.offset = end;
+		Ok(bytes)
+	}
+
+	fn array<const N: usize>(&mut self) -> EggResult<[u8; N]> {
+		Ok(self.take(N)?.try_into().expect("Took exactly N bytes"))
+	}
+
+	fn usize(&mut self) -> EggResult<usize> {
+		let mut value = 0usize;
+
+		for shift in (0..usize::BITS).step_by(7) {
+			let [byte] = self.array()?;
+			value |= ((byte & 0x7f) as usize).checked_shl(shift).unwrap_or_default();
+
+			if byte & 0x80 == 0 {
+				return Ok(value);
+			}
+		}
+
+		Err(invalid(format!("Integer too large at byte {}", self.offset)))
+	}
+
+	fn str(&mut self) -> EggResult<ArcStr> {
+		let length = self.usize()?;
+		let offset = self.offset;
+
+		core::str::from_utf8(self.take(length)?)
+			.map(ArcStr::from)
+			.map_err(|_| invalid(format!("Invalid UTF-8 in string at byte {offset}")))
+	}
+
+	fn span(&mut self) -> EggResult<Range<usize>> {
+		Ok(self.usize()?..self.usize()?)
+	}
+
+	fn expression(&mut self, operators: &BTreeMap<&str, Box<dyn Operator>>, depth: usize) -> EggResult<Expression> {
+		let offset = self.offset;
+		if depth > MAX_DEPTH {
+			return Err(invalid(format!("Expressions nested deeper than {MAX_DEPTH} at byte {offset}")));
+		}
+
+		let [tag] = self.array()?;
+
+		let value = match tag {
+			NIL => Value::Nil,
+			NUMBER => Value::Number(f32::from_le_bytes(self.array()?).into()),
+			BOOLEAN => Value::Boolean(self.array::<1>()?[0] != 0),
+			STRING => Value::String(self.str()?),
+			WORD => {
+				let name = self.str()?;
+				return Ok(Expression::Word { name, span: self.span()? });
+			}
+			HOST_CALL | SCRIPT_CALL => {
+				let name = self.str()?;
+				let function = match operators.get(name.as_str()) {
+					Some(op) if tag == HOST_CALL => Function::Host(name, op.as_ref() as _),
+					None if tag == HOST_CALL => return Err(invalid(format!("Builtin `{name}` is not registered"))),
+					_ => Function::Script(name),
+				};
+
+				let count = self.usize()?;
+				let mut parameters = Vec::with_capacity(count.min(self.bytes.len() - self.offset));
+				for _ in 0..count {
+					parameters.push(self.expression(operators, depth + 1)?);
+				}
+
+				return Ok(Expression::FnCall {
+					function,
+					parameters,
+					span: self.span()?,
+				});
+			}
+			tag => return Err(invalid(format!("Unknown expression tag {tag} at byte {offset}"))),
+		};
+
+		Ok(Expression::Value { value, span: self.span()? })
+	}
+}
+
+/// Serializes a parsed script into JSON, with the same structure and validation as [`to_bytes`].
+///
+/// Expressions are objects with a `span` of `[start, end]`, and either a literal `value`, a `word`, or a `call` with it's `parameters`.
+/// Numbers must be finite, as JSON can't represent infinities or NaN.
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::precompiled;
+///
+/// let mut operators = operators::empty();
+/// operators::minimal(&mut operators);
+///
+/// let script = parse("sum(x, 1)", &operators).unwrap();
+/// let json = precompiled::to_json(&script).unwrap();
+/// assert_eq!(
+///     json.to_string(),
+///     r#"{"expressions":[{"builtin":true,"call":"sum","parameters":[{"span":[4,5],"word":"x"},{"span":[7,8],"value":1.0}],"span":[0,9]}],"version":1}"#
+/// );
+///
+/// let loaded = precompiled::from_json(&json, &operators).unwrap();
+/// assert_eq!(format!("{loaded:?}"), format!("{script:?}"));
+/// ```
+#[cfg(feature = "json")]
+pub fn to_json(expressions: &[Expression]) -> EggResult<serde_json::Value> {
+	fn expression(expr: &Expression) -> EggResult<serde_json::Value> {
+		let span = expr.span();
+		let span = serde_json::json!([span.start, span.end]);
+
+		Ok(match expr {
+			Expression::Value { value, .. } => {
+				let value = match value {
+					Value::Nil => serde_json::Value::Null,
+					Value::Number(n) if n.0.is_finite() => serde_json::json!(n.0),
+					Value::Boolean(b) => serde_json::json!(b),
+					Value::String(s) => serde_json::json!(s.as_str()),
+					value => return Err(invalid(format!("Cannot represent {value} in JSON"))),
+				};
+				serde_json::json!({ "value": value, "span": span })
+			}
+			Expression::Word { name, .. } => serde_json::json!({ "word": name.as_str(), "span": span }),
+			Expression::FnCall { function, parameters, .. } => {
+				let (name, builtin) = match function {
+					Function::Host(name, _) => (name, true),
+					Function::Script(name) => (name, false),
+				};
+				let parameters = parameters.iter().map(expression).collect::<EggResult<Vec<_>>>()?;
+
+				serde_json::json!({ "call": name.as_str(), "builtin": builtin, "parameters": parameters, "span": span })
+			}
+		})
+	}
+
+	let expressions = expressions.iter().map(expression).collect::<EggResult<Vec<_>>>()?;
+	Ok(serde_json::json!({ "version": VERSION, "expressions": expressions }))
+}
+
+/// Loads a script serialized by [`to_json`], relinking builtins against `operators`
+#[cfg(feature = "json")]
+pub fn from_json(json: &serde_json::Value, operators: &BTreeMap<&str, Box<dyn Operator>>) -> EggResult<Vec<Expression>> {
+	fn expression(json: &serde_json::Value, operators: &BTreeMap<&str, Box<dyn Operator>>, depth: usize) -> EggResult<Expression> {
+		if depth > MAX_DEPTH {
+			return Err(invalid(format!("Expressions nested deeper than {MAX_DEPTH}")));
+		}
+
+		let span = match json["span"].as_array().map(Vec::as_slice) {
+			Some([start, end]) => match (start.as_u64(), end.as_u64()) {
+				(Some(start), Some(end)) => start as usize..end as usize,
+				_ => return Err(invalid(format!("Invalid span in {json}"))),
+			},
+			_ => return Err(invalid(format!("Missing span in {json}"))),
+		};
+
+		if let Some(value) = json.get("value") {
+			let value = match value {
+				serde_json::Value::Null => Value::Nil,
+				serde_json::Value::Bool(b) => Value::Boolean(*b),
+				serde_json::Value::Number(n) => Value::from(n.as_f64().unwrap_or_default() as f32),
+				serde_json::Value::String(s) => Value::from(s.as_str()),
+				value => return Err(invalid(format!("Invalid value {value}"))),
+			};
+			return Ok(Expression::Value { value, span });
+		}
+
+		if let Some(name) = json["word"].as_str() {
+			return Ok(Expression::Word { name: name.into(), span });
+		}
+
+		let (Some(name), Some(parameters)) = (json["call"].as_str(), json["parameters"].as_array()) else {
+			return Err(invalid(format!("Expected a value, word or call, found {json}")));
+		};
+
+		let function = match (operators.get(name), json["builtin"].as_bool().unwrap_or_default()) {
+			(Some(op), true) => Function::Host(name.into(), op.as_ref() as _),
+			(None, true) => return Err(invalid(format!("Builtin `{name}` is not registered"))),
+			(_, false) => Function::Script(name.into()),
+		};
+		let parameters = parameters.iter().map(|parameter| expression(parameter, operators, depth + 1)).collect::<EggResult<_>>()?;
+
+		Ok(Expression::FnCall { function, parameters, span })
+	}
+
+	match json["version"].as_u64() {
+		Some(version) if version == VERSION as u64 => {}
+		Some(version) => return Err(invalid(format!("Unsupported version {version}, expected {VERSION}"))),
+		None => return Err(invalid("Missing version")),
+	}
+
+	let expressions = json["expressions"].as_array().ok_or_else(|| invalid("Missing expressions"))?;
+	expressions.iter().map(|expr| expression(expr, operators, 0)).collect()
+}
@@ -0,0 +1,180 @@
+use alloc::{collections::BTreeMap, format, string::String, vec::Vec};
+use arcstr::ArcStr;
+use core::fmt::Write;
+use std::time::{Duration, Instant};
+
+use crate::scope::Scope;
+
+/// Identifies a profiled function: either an Egg function or a host [`Operator`](crate::operators::Operator).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FrameKey {
+	/// A function defined in Egg, keyed by the name it was called with and the `(start, end)` byte offsets of it's definition
+	Function { name: ArcStr, span: (usize, usize) },
+	/// A builtin, keyed by the name it was invoked with
+	Operator { name: ArcStr },
+}
+
+impl FrameKey {
+	/// The name the function was invoked with
+	pub fn name(&self) -> &str {
+		match self {
+			FrameKey::Function { name, .. } | FrameKey::Operator { name } => name.as_str(),
+		}
+	}
+}
+
+/// Timings collected for a single [`FrameKey`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProfileEntry {
+	/// How many times the function was called
+	pub calls: u64,
+	/// Time spent in the function, including time spent in functions it called. Recursive calls are only counted once.
+	pub inclusive: Duration,
+	/// Time spent in the function itself
+	pub exclusive: Duration,
+}
+
+/// The results of a profiling session, see [`Scope::start_profiling`].
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+	/// Timings for every function called while profiling
+	pub entries: BTreeMap<FrameKey, ProfileEntry>,
+	/// Exclusive time spent in each unique call stack, frames separated by `;`
+	pub stacks: BTreeMap<String, Duration>,
+}
+
+impl Profile {
+	/// Entries sorted by exclusive time, most expensive first
+	pub fn hottest(&self) -> Vec<(&FrameKey, &ProfileEntry)> {
+		let mut entries = self.entries.iter().collect::<Vec<_>>();
+		entries.sort_by_key(|(_, entry)| core::cmp::Reverse(entry.exclusive));
+		entries
+	}
+
+	/// Render the call stacks in the collapsed format consumed by flamegraph tools.
+	///
+	/// Each line is a `;` separated stack followed by the exclusive time spent in it in microseconds, in place of a sample count: `fact;multiply;fact 42`
+	pub fn collapsed(&self) -> String {
+		self.stacks.iter().fold(String::new(), |mut output, (stack, time)| {
+			let _ = writeln!(output, "{stack} {}", time.as_micros());
+			output
+		})
+	}
+
+	/// Render a human readable table of timings, sorted by exclusive time
+	pub fn report(&self) -> String {
+		let mut output = format!("{:<32} {:>10} {:>14} {:>14}\n", "Function", "Calls", "Inclusive", "Exclusive");
+
+		for (key, entry) in self.hottest() {
+			let name = match key {
+				FrameKey::Function { name, span: (start, end) } => format!("{name} @ {start}..{end}"),
+				FrameKey::Operator { name } => format!("{name} (builtin)"),
+			};
+
+			let _ = writeln!(output, "{name:<32} {:>10} {:>14?} {:>14?}", entry.calls, entry.inclusive, entry.exclusive);
+		}
+
+		output
+	}
+}
+
+#[derive(Debug)]
+struct Frame {
+	key: FrameKey,
+	start: Instant,
+	children: Duration,
+}
+
+/// Records timings as frames are entered and exited by the evaluator
+#[derive(Debug, Default)]
+pub(crate) struct Profiler {
+	stack: Vec<Frame>,
+	profile: Profile,
+}
+
+impl Profiler {
+	fn enter(&mut self, key: FrameKey) {
+		self.stack.push(Frame {
+			key,
+			start: Instant::now(),
+			children: Duration::ZERO,
+		});
+	}
+
+	fn exit(&mut self) {
+		let Some(frame) = self.stack.pop() else { return };
+
+		let elapsed = frame.start.elapsed();
+		let exclusive = elapsed.saturating_sub(frame.children);
+
+		if let Some(parent) = self.stack.last_mut() {
+			parent.children += elapsed;
+		}
+
+		let path = self.stack.iter().chain(core::iter::once(&frame)).map(|f| f.key.name()).collect::<Vec<_>>().join(";");
+		*self.profile.stacks.entry(path).or_default() += exclusive;
+
+		let recursive = self.stack.iter().any(|f| f.key == frame.key);
+		let entry = self.profile.entries.entry(frame.key).or_default();
+
+		entry.calls += 1;
+		entry.exclusive += exclusive;
+		if !recursive {
+			entry.inclusive += elapsed;
+		}
+	}
+}
+
+impl Scope {
+	/// Start recording timings for every Egg function and builtin called. Discards any profile currently being recorded.
+	///
+	/// ```
+	/// use egglang::prelude::*;
+	///
+	/// let mut scope = Scope::default();
+	/// let mut operators = operators::empty();
+	/// operators::minimal(&mut operators);
+	///
+	/// let script = parse("define(square, fn(x, multiply(x x))) square(4)", &operators).unwrap();
+	///
+	/// scope.start_profiling();
+	/// script.iter().for_each(|expr| drop(evaluate(expr, &mut scope)));
+	/// let profile = scope.stop_profiling().unwrap();
+	///
+	/// assert!(profile.collapsed().contains("square;multiply"));
+	/// ```
+	pub fn start_profiling(&mut self) {
+		self.extras_mut().profiler = Some(Profiler::default());
+	}
+
+	/// Stop profiling, returning the recorded [`Profile`] if profiling was active
+	pub fn stop_profiling(&mut self) -> Option<Profile> {
+		self.extras_mut().profiler.take().map(|profiler| profiler.profile)
+	}
+
+	#[inline]
+	pub(crate) fn profile_enter_function(&mut self, name: &ArcStr, idx: usize) {
+		if self.extras().profiler.is_none() {
+			return;
+		}
+
+		let span = self.get_function_definition(idx).map(|f| (f.span.start, f.span.end)).unwrap_or_default();
+		if let Some(profiler) = self.extras_mut().profiler.as_mut() {
+			profiler.enter(FrameKey::Function { name: name.clone(), span });
+		}
+	}
+
+	#[inline]
+	pub(crate) fn profile_enter_operator(&mut self, name: &ArcStr) {
+		if let Some(profiler) = self.extras_mut().profiler.as_mut() {
+			profiler.enter(FrameKey::Operator { name: name.clone() });
+		}
+	}
+
+	#[inline]
+	pub(crate) fn profile_exit(&mut self) {
+		if let Some(profiler) = self.extras_mut().profiler.as_mut() {
+			profiler.exit();
+		}
+	}
+}
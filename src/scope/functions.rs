@@ -1,5 +1,6 @@
 use alloc::{collections::BTreeMap, format, string::ToString, vec::Vec};
 use arcstr::ArcStr;
+use core::ops::Range;
 
 use crate::{
 	error::{EggError, EggResult},
@@ -20,6 +21,8 @@ pub struct FunctionDefinition {
 	pub parameter_names: Vec<ArcStr>,
 	/// The body of the function.
 	pub body: Expression,
+	/// The byte range spanning the function's parameters and body, in the script it was defined in.
+	pub span: Range<usize>,
 }
 
 impl core::fmt::Debug for FunctionDefinition {
@@ -33,7 +36,7 @@ impl core::fmt::Debug for FunctionDefinition {
 
 fn get_parameter_name(expr: &Expression) -> EggResult<ArcStr> {
 	match expr {
-		Expression::Word { name } => Ok(name.clone()),
+		Expression::Word { name, .. } => Ok(name.clone()),
 		_ => Err(EggError::InvalidFunctionDefinition("Parameter name must be a word".to_string())),
 	}
 }
@@ -61,7 +64,13 @@ impl super::Scope {
 			.ok_or_else(|| EggError::InvalidFunctionCall(format!("Function with index {} not found", idx)))
 	}
 
+	/// Call the function stored at `idx`, evaluating `parameters` in this scope as it's arguments
 	pub fn call_function(&mut self, idx: usize, parameters: &[Expression]) -> EggResult<Value> {
+		self.call_named_function(&arcstr::literal!("<anonymous>"), idx, parameters)
+	}
+
+	/// Call a function, with the name it was invoked with. The name is reported to [`Hooks`](crate::hooks::Hook)
+	pub(crate) fn call_named_function(&mut self, name: &ArcStr, idx: usize, parameters: &[Expression]) -> EggResult<Value> {
 		let function = unsafe {
 			// SAFETY: We are not modifying the scope, only reading the function definition from it
 			(*(self as *const Scope)).get_function_definition(idx)?
@@ -75,18 +84,35 @@ impl super::Scope {
 			)));
 		}
 
-		let mut new_scope = BTreeMap::new();
-		for (name, expression) in function.parameter_names.iter().zip(parameters.iter()) {
-			let value = evaluate(expression, self)?;
-			new_scope.insert(name.clone(), value);
-		}
+		let arguments = parameters.iter().map(|expression| evaluate(expression, self)).collect::<EggResult<Vec<_>>>()?;
+		self.run_hooks(|hook, scope| hook.on_call(name, &arguments, scope));
+
+		let new_scope = function.parameter_names.iter().cloned().zip(arguments).collect::<BTreeMap<_, _>>();
+
+		// Arguments are evaluated by the caller, so they are profiled as part of it
+		#[cfg(feature = "std")]
+		self.profile_enter_function(name, idx);
 
+		self.enter_function();
+		self.hold_strings(new_scope.values());
 		let mut local_scope = self.local(new_scope);
-		evaluate(&function.body, &mut local_scope)
+		let result = evaluate(&function.body, &mut local_scope);
+
+		// Variables of the function are dropped along with it's scope
+		self.release_strings(local_scope.bindings().values());
+		self.exit_function();
+
+		#[cfg(feature = "std")]
+		self.profile_exit();
+		self.run_hooks(|hook, scope| hook.on_return(name, &result, scope));
+		result
 	}
 
 	pub fn delete_function(&mut self, idx: usize) -> Option<FunctionDefinition> {
-		self.extras_mut().functions.remove(&idx)
+		let function = self.extras_mut().functions.remove(&idx)?;
+
+		self.release_live_value();
+		Some(function)
 	}
 }
 
@@ -102,11 +128,17 @@ impl Operator for CreateFunction {
 		// assemble function parts
 		let body = args[args.len() - 1].clone();
 		let parameter_names = args.iter().take(args.len() - 1).map(get_parameter_name).collect::<EggResult<Vec<ArcStr>>>()?;
+		let span = args[0].span().start..body.span().end;
 
+		scope.allocate_live_value()?;
 		scope.extras_mut().counter += 1;
 		let index = scope.extras().counter;
-		scope.extras_mut().functions.insert(index, FunctionDefinition { parameter_names, body });
+		scope.extras_mut().functions.insert(index, FunctionDefinition { parameter_names, body, span });
 
 		Ok(crate::expression::Value::Function(index))
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("fn(parameters..., body)\n\nCreates a function. The last argument is it's body, the rest name it's parameters.")
+	}
 }
@@ -0,0 +1,103 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{
+	error::{EggError, EggResult},
+	scope::Scope,
+};
+
+/// How many calls to [`evaluate`](crate::evaluator::evaluate) pass between checks for cancellation and timeouts.
+pub(crate) const INTERRUPT_INTERVAL: u64 = 1024;
+
+/// A flag shared between a host and a running script, used to cooperatively stop evaluation.
+///
+/// Clone the token, install one copy in the [`Scope`] using [`Scope::set_cancellation_token`] and call [`cancel`](CancellationToken::cancel) on the other from any thread.
+/// The evaluator then fails with [`EggError::Cancelled`] at the next check.
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::scope::CancellationToken;
+///
+/// let mut operators = operators::empty();
+/// operators::minimal(&mut operators);
+///
+/// let token = CancellationToken::new();
+/// let mut scope = Scope::default();
+/// scope.set_cancellation_token(Some(token.clone()));
+///
+/// token.cancel();
+/// let script = parse("while(true, Nil)", &operators).unwrap();
+/// assert!(matches!(evaluate(&script[0], &mut scope), Err(EggError::Cancelled)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+	/// Create a new token, that is not cancelled
+	pub fn new() -> CancellationToken {
+		Default::default()
+	}
+
+	/// Request that any evaluation observing this token stops
+	pub fn cancel(&self) {
+		self.0.store(true, Ordering::Relaxed);
+	}
+
+	/// Has cancellation been requested?
+	pub fn is_cancelled(&self) -> bool {
+		self.0.load(Ordering::Relaxed)
+	}
+
+	/// Clear a previous cancellation, allowing the token to be reused
+	pub fn reset(&self) {
+		self.0.store(false, Ordering::Relaxed);
+	}
+}
+
+impl Scope {
+	/// Install a token that the host can use to cancel evaluation. Pass `None` to remove it.
+	pub fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+		self.extras_mut().cancellation = token;
+	}
+
+	/// Set a wall-clock deadline, after which evaluation fails with [`EggError::Timeout`]. Pass `None` to remove it.
+	#[cfg(feature = "std")]
+	pub fn set_deadline(&mut self, deadline: Option<std::time::Instant>) {
+		self.extras_mut().deadline = deadline;
+	}
+
+	/// Set a deadline `timeout` from now. See [`set_deadline`](Scope::set_deadline).
+	#[cfg(feature = "std")]
+	pub fn set_timeout(&mut self, timeout: core::time::Duration) {
+		self.set_deadline(Some(std::time::Instant::now() + timeout));
+	}
+
+	/// Fails if evaluation has been cancelled or has exceeded it's deadline.
+	/// Long running operators should call this periodically.
+	pub fn check_interrupts(&self) -> EggResult<()> {
+		let extras = self.extras();
+
+		if extras.cancellation.as_ref().is_some_and(|token| token.is_cancelled()) {
+			return Err(EggError::Cancelled);
+		}
+
+		#[cfg(feature = "std")]
+		if extras.deadline.is_some_and(|deadline| std::time::Instant::now() >= deadline) {
+			return Err(EggError::Timeout);
+		}
+
+		Ok(())
+	}
+
+	/// Count an evaluation, checking for interrupts every [`INTERRUPT_INTERVAL`] evaluations
+	#[inline]
+	pub(crate) fn tick(&mut self) -> EggResult<()> {
+		let statistics = &mut self.extras_mut().statistics;
+		statistics.evaluations = statistics.evaluations.wrapping_add(1);
+
+		match statistics.evaluations % INTERRUPT_INTERVAL {
+			0 => self.check_interrupts(),
+			_ => Ok(()),
+		}
+	}
+}
@@ -0,0 +1,127 @@
+use alloc::format;
+
+use crate::{
+	error::{EggError, EggResult},
+	expression::Value,
+	scope::Scope,
+};
+
+/// Memory quotas enforced by a [`Scope`]. A quota of `None` is unbounded, which is the default.
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::scope::Limits;
+///
+/// let mut scope = Scope::default();
+/// scope.set_limits(Limits { string_bytes: Some(8), ..Default::default() });
+///
+/// let mut operators = operators::empty();
+/// operators::full(&mut operators);
+///
+/// let script = parse(r#"string.concat("Hello, " "World!")"#, &operators).unwrap();
+/// let result = evaluate(&script[0], &mut scope);
+/// assert!(matches!(result, Err(EggError::MemoryLimitExceeded(_))));
+///
+/// // Strings are only accounted for while they are held
+/// let script = parse(r#"define(x, "") repeat(10, set(x, string.concat("Hi", "!")))"#, &operators).unwrap();
+/// script.iter().for_each(|expr| drop(evaluate(expr, &mut scope).unwrap()));
+/// assert_eq!(scope.usage().string_bytes, 3);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+	/// Bytes of string data held by variables and object entries at any one time, a string held in several places is counted for each.
+	/// Builtins fail to build a new string, eg: through `string.concat`, if it would not fit alongside them.
+	pub string_bytes: Option<usize>,
+	/// Total number of entries stored across all live objects.
+	pub object_entries: Option<usize>,
+	/// Number of functions and objects alive at any one time.
+	pub live_values: Option<usize>,
+}
+
+/// Resources currently accounted against a [`Scope`]'s [`Limits`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+	/// Bytes of string data held by variables and object entries.
+	pub string_bytes: usize,
+	/// Entries stored across all live objects.
+	pub object_entries: usize,
+	/// Functions and objects currently alive.
+	pub live_values: usize,
+}
+
+/// Bytes of string data held by a value
+fn string_size(value: &Value) -> usize {
+	match value {
+		Value::String(string) => string.len(),
+		_ => 0,
+	}
+}
+
+fn charge(used: &mut usize, amount: usize, limit: Option<usize>, resource: &str) -> EggResult<()> {
+	let total = used.saturating_add(amount);
+
+	match limit {
+		Some(limit) if total > limit => Err(EggError::MemoryLimitExceeded(format!("{resource} would grow to {total}, the limit is {limit}"))),
+		_ => {
+			*used = total;
+			Ok(())
+		}
+	}
+}
+
+impl Scope {
+	/// Replace the memory quotas of this scope. Resources already in use are not released.
+	pub fn set_limits(&mut self, limits: Limits) {
+		self.extras_mut().limits = limits;
+	}
+
+	/// Current memory quotas
+	pub fn limits(&self) -> Limits {
+		self.extras().limits
+	}
+
+	/// Resources currently accounted against the memory quotas
+	pub fn usage(&self) -> Usage {
+		self.extras().usage
+	}
+
+	/// Check that `bytes` of newly built string data fit alongside the strings already held, failing if [`Limits::string_bytes`] would be exceeded.
+	/// Operators producing new strings should call this before returning them.
+	pub fn allocate_string(&mut self, bytes: usize) -> EggResult<()> {
+		let extras = self.extras();
+		let mut held = extras.usage.string_bytes;
+		charge(&mut held, bytes, extras.limits.string_bytes, "String data (bytes)")
+	}
+
+	/// Account for strings stored in variables or object entries, until they are released
+	pub(crate) fn hold_strings<'a>(&mut self, values: impl IntoIterator<Item = &'a Value>) {
+		let usage = &mut self.extras_mut().usage;
+		usage.string_bytes = values.into_iter().map(string_size).fold(usage.string_bytes, usize::saturating_add);
+	}
+
+	/// Stop accounting for strings removed from variables or object entries
+	pub(crate) fn release_strings<'a>(&mut self, values: impl IntoIterator<Item = &'a Value>) {
+		let usage = &mut self.extras_mut().usage;
+		usage.string_bytes = values.into_iter().map(string_size).fold(usage.string_bytes, usize::saturating_sub);
+	}
+
+	pub(crate) fn allocate_object_entries(&mut self, count: usize) -> EggResult<()> {
+		let extras = self.extras_mut();
+		charge(&mut extras.usage.object_entries, count, extras.limits.object_entries, "Object entries")
+	}
+
+	pub(crate) fn release_object_entries(&mut self, count: usize) {
+		let usage = &mut self.extras_mut().usage;
+		usage.object_entries = usage.object_entries.saturating_sub(count);
+	}
+
+	pub(crate) fn allocate_live_value(&mut self) -> EggResult<()> {
+		let extras = self.extras_mut();
+		charge(&mut extras.usage.live_values, 1, extras.limits.live_values, "Live functions and objects")
+	}
+
+	pub(crate) fn release_live_value(&mut self) {
+		let usage = &mut self.extras_mut().usage;
+		usage.live_values = usage.live_values.saturating_sub(1);
+	}
+}
@@ -1,4 +1,4 @@
-use alloc::{collections::BTreeMap, format};
+use alloc::{boxed::Box, collections::BTreeMap, format};
 use arcstr::ArcStr;
 
 use crate::{
@@ -7,7 +7,14 @@ use crate::{
 };
 
 pub(crate) mod functions;
+mod interrupt;
+mod limits;
 pub(crate) mod object;
+mod statistics;
+
+pub use interrupt::CancellationToken;
+pub use limits::{Limits, Usage};
+pub use statistics::Statistics;
 
 /// A [`Scope`] is responsible for keeping track of script state.
 ///
@@ -30,8 +37,15 @@ pub(crate) mod object;
 #[derive(Debug)]
 #[allow(private_interfaces)]
 pub enum Scope {
-	Global { source: BTreeMap<ArcStr, Value>, extras: Extras },
-	Local { overlay: BTreeMap<ArcStr, Value>, source: *mut Scope },
+	Global {
+		source: BTreeMap<ArcStr, Value>,
+		extras: Box<Extras>,
+	},
+	Local {
+		overlay: BTreeMap<ArcStr, Value>,
+		source: *mut Scope,
+		extras: *mut Extras,
+	},
 }
 
 impl Default for Scope {
@@ -92,35 +106,44 @@ impl Scope {
 			return Err(EggError::OperatorComplaint(format!("Variable {} already defined", key)));
 		}
 
+		self.run_hooks(|hook, scope| hook.on_define(&key, &value, scope));
+		self.insert_binding(key, value);
+		Ok(())
+	}
+
+	fn insert_binding(&mut self, key: ArcStr, value: Value) {
+		self.hold_strings([&value]);
+
 		match self {
 			Scope::Global { source, .. } => source.insert(key, value),
 			Scope::Local { overlay, .. } => overlay.insert(key, value),
 		};
-
-		Ok(())
 	}
 
 	/// Updates the value of a variable if it is in the present scope, otherwise updates it in the parent scope.
 	pub fn update(&mut self, key: ArcStr, value: Value) {
+		self.run_hooks(|hook, scope| hook.on_set(&key, &value, scope));
+
 		let was_local = matches!(self, Scope::Local { overlay, .. } if overlay.contains_key(&key));
-		self.delete(&key);
+		self.delete_binding(&key);
 
 		match self {
-			Scope::Global { source, .. } => {
-				source.insert(key, value);
-			}
-			Scope::Local { overlay, source } => {
-				if was_local {
-					overlay.insert(key, value);
-				} else {
-					unsafe { source.as_mut().map(|s| s.insert(key, value)) };
+			Scope::Local { source, .. } if !was_local => {
+				if let Some(source) = unsafe { source.as_mut() } {
+					source.insert_binding(key, value)
 				}
 			}
-		};
+			_ => self.insert_binding(key, value),
+		}
 	}
 
 	/// Delete a variable if it is the present scope, otherwise delete it from the parent scope.
 	pub fn delete(&mut self, key: &str) -> Option<Value> {
+		self.run_hooks(|hook, scope| hook.on_delete(key, scope));
+		self.delete_binding(key)
+	}
+
+	fn delete_binding(&mut self, key: &str) -> Option<Value> {
 		if let Some(Value::Function(index)) = self.get(key) {
 			self.delete_function(*index);
 		}
@@ -129,22 +152,45 @@ impl Scope {
 			self.delete_object(*tag);
 		}
 
+		let removed = self.remove_binding(key);
+		self.release_strings(&removed);
+		removed
+	}
+
+	fn remove_binding(&mut self, key: &str) -> Option<Value> {
 		match self {
 			Scope::Global { source, .. } => source.remove(key),
-			Scope::Local { overlay, source: parent, .. } => unsafe { overlay.remove(key).or_else(|| parent.as_mut().and_then(|p| p.delete(key))) },
+			Scope::Local { overlay, source: parent, .. } => unsafe { overlay.remove(key).or_else(|| parent.as_mut().and_then(|p| p.remove_binding(key))) },
+		}
+	}
+
+	/// Variables defined in the current scope, excluding those of parent scopes.
+	pub fn bindings(&self) -> &BTreeMap<ArcStr, Value> {
+		match self {
+			Scope::Global { source, .. } => source,
+			Scope::Local { overlay, .. } => overlay,
+		}
+	}
+
+	/// Variables defined in the global scope, at the root of the scope chain.
+	pub fn global_bindings(&self) -> &BTreeMap<ArcStr, Value> {
+		match self {
+			Scope::Global { source, .. } => source,
+			Scope::Local { source, .. } => unsafe { source.as_ref().map(|s| s.global_bindings()).unwrap_unchecked() },
 		}
 	}
 
-	/// Create a new local scope.
+	/// Create a new local scope. It points directly at the [`Extras`] of the global scope, rather than reaching them through every parent.
 	pub(crate) fn local(&mut self, overlay: BTreeMap<ArcStr, Value>) -> Scope {
-		Scope::Local { overlay, source: self as _ }
+		let extras = self.extras_mut() as *mut Extras;
+		Scope::Local { overlay, source: self as _, extras }
 	}
 
 	/// Get extra metadata attached to the scope.
 	pub(crate) fn extras(&self) -> &Extras {
 		match self {
 			Scope::Global { extras, .. } => extras,
-			Scope::Local { source, .. } => unsafe { source.as_ref().map(|s| s.extras()).unwrap_unchecked() },
+			Scope::Local { extras, .. } => unsafe { &**extras },
 		}
 	}
 
@@ -152,7 +198,7 @@ impl Scope {
 	pub(crate) fn extras_mut(&mut self) -> &mut Extras {
 		match self {
 			Scope::Global { extras, .. } => extras,
-			Scope::Local { source, .. } => unsafe { source.as_mut().map(|s| s.extras_mut()).unwrap_unchecked() },
+			Scope::Local { extras, .. } => unsafe { &mut **extras },
 		}
 	}
 }
@@ -163,5 +209,17 @@ pub(crate) struct Extras {
 	maps: BTreeMap<usize, BTreeMap<Value, Value>>,
 	functions: BTreeMap<usize, functions::FunctionDefinition>,
 	counter: usize,
+	limits: Limits,
+	usage: Usage,
+	cancellation: Option<CancellationToken>,
+	#[cfg(feature = "std")]
+	deadline: Option<std::time::Instant>,
+	statistics: Statistics,
+	operator_statistics: bool,
+	call_depth: usize,
+	#[cfg(feature = "std")]
+	pub(crate) profiler: Option<crate::profiler::Profiler>,
+	pub(crate) hooks: crate::hooks::Hooks,
+	pub(crate) streams: crate::io::Streams,
 	_unsend: core::marker::PhantomData<*mut ()>,
 }
@@ -11,6 +11,8 @@ use crate::{
 
 impl Scope {
 	pub fn create_object(&mut self) -> EggResult<Value> {
+		self.allocate_live_value()?;
+		self.extras_mut().statistics.objects_allocated += 1;
 		self.extras_mut().counter += 1;
 		let index = self.extras().counter;
 
@@ -22,7 +24,7 @@ impl Scope {
 		let Value::Object(idx) = &tag else { return Err(EggError::InvalidObjectReference(tag)) };
 
 		match self.extras().maps.contains_key(idx) {
-			true => Ok(idx.clone()),
+			true => Ok(*idx),
 			false => Err(EggError::InvalidObjectReference(tag)),
 		}
 	}
@@ -37,9 +39,23 @@ impl Scope {
 		self.extras_mut().maps.get_mut(&tag).expect("Object Not Found")
 	}
 
+	/// Add entries to an object built by a builtin, accounting for their strings. The entries should already be allocated.
+	pub(crate) fn extend_object(&mut self, tag: usize, entries: impl IntoIterator<Item = (Value, Value)>) {
+		for (key, value) in entries {
+			self.hold_strings([&key, &value]);
+			let previous = self.get_object_mut(tag).insert(key, value);
+			self.release_strings(&previous);
+		}
+	}
+
 	#[inline]
 	pub fn delete_object(&mut self, tag: usize) -> Option<BTreeMap<Value, Value>> {
-		self.extras_mut().maps.remove(&tag)
+		let map = self.extras_mut().maps.remove(&tag)?;
+
+		self.release_object_entries(map.len());
+		self.release_strings(map.iter().flat_map(|(key, value)| [key, value]));
+		self.release_live_value();
+		Some(map)
 	}
 }
 
@@ -50,6 +66,10 @@ impl Operator for CreateObject {
 	fn evaluate(&self, _: &[Expression], scope: &mut Scope) -> EggResult<Value> {
 		scope.create_object()
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("object.new()\n\nCreates a new, empty object.")
+	}
 }
 
 /// Insert a new value into the specified map
@@ -71,9 +91,20 @@ impl Operator for Insert {
 		let value = evaluate(&args[2], scope)?;
 
 		let tag = scope.get_object_tag(tag)?;
-		let map = scope.get_object_mut(tag);
+		if !scope.get_object(tag).contains_key(&key) {
+			scope.allocate_object_entries(1)?;
+			scope.hold_strings([&key]);
+		}
+
+		scope.hold_strings([&value]);
+		let previous = scope.get_object_mut(tag).insert(key, value);
+		scope.release_strings(&previous);
+
+		Ok(previous.into())
+	}
 
-		Ok(map.insert(key, value).into())
+	fn documentation(&self) -> Option<&'static str> {
+		Some("object.insert(object, key, value)\n\nInserts a value into an object, yielding the previous value. Keys must be primitives.")
 	}
 }
 
@@ -92,6 +123,10 @@ impl Operator for Get {
 
 		Ok(map.get(&key).cloned().into())
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("object.get(object, key)\n\nFetches the value stored at `key`, or Nil.")
+	}
 }
 
 /// Check if the specified map contains the key
@@ -109,6 +144,10 @@ impl Operator for Has {
 
 		Ok(map.contains_key(&key).into())
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("object.has(object, key)\n\nTrue if the object contains `key`.")
+	}
 }
 
 /// Delete the given key at the given map
@@ -122,9 +161,17 @@ impl Operator for Remove {
 		let key = evaluate(&args[1], scope)?;
 
 		let tag = scope.get_object_tag(tag)?;
-		let map = scope.get_object_mut(tag);
+		let Some((key, value)) = scope.get_object_mut(tag).remove_entry(&key) else {
+			return Ok(Value::Nil);
+		};
+
+		scope.release_object_entries(1);
+		scope.release_strings([&key, &value]);
+		Ok(value)
+	}
 
-		Ok(map.remove(&key).into())
+	fn documentation(&self) -> Option<&'static str> {
+		Some("object.remove(object, key)\n\nRemoves a key from an object, yielding it's value.")
 	}
 }
 
@@ -141,6 +188,10 @@ impl Operator for Size {
 		let map = scope.get_object(tag);
 		Ok((map.len() as f32).into())
 	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("object.size(object)\n\nThe number of entries in an object.")
+	}
 }
 
 /// Clear the specified map
@@ -153,10 +204,13 @@ impl Operator for Clear {
 		let tag = evaluate(&args[0], scope)?;
 		let tag = scope.get_object_tag(tag)?;
 
-		let map = scope.get_object_mut(tag);
-		Ok({
-			map.clear();
-			().into()
-		})
+		let map = core::mem::take(scope.get_object_mut(tag));
+		scope.release_object_entries(map.len());
+		scope.release_strings(map.iter().flat_map(|(key, value)| [key, value]));
+		Ok(().into())
+	}
+
+	fn documentation(&self) -> Option<&'static str> {
+		Some("object.clear(object)\n\nRemoves all entries from an object.")
 	}
 }
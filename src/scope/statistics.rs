@@ -0,0 +1,88 @@
+use alloc::collections::BTreeMap;
+use arcstr::ArcStr;
+
+use crate::scope::Scope;
+
+/// Execution statistics collected by a [`Scope`] while scripts are evaluated.
+///
+/// ```
+/// use egglang::prelude::*;
+///
+/// let mut scope = Scope::default();
+/// scope.set_operator_statistics(true);
+///
+/// let mut operators = operators::empty();
+/// operators::minimal(&mut operators);
+///
+/// let script = parse("sum(1 multiply(2 5))", &operators).unwrap();
+/// evaluate(&script[0], &mut scope).unwrap();
+///
+/// let statistics = scope.take_statistics();
+/// assert_eq!(statistics.evaluations, 5);
+/// assert_eq!(statistics.operator_calls["multiply"], 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Statistics {
+	/// Calls to [`evaluate`](crate::evaluator::evaluate), including internal calls
+	pub evaluations: u64,
+	/// Calls to functions defined in Egg
+	pub function_calls: u64,
+	/// Calls to each [`Operator`](crate::operators::Operator), keyed by the name it was invoked with.
+	/// Only counted after [`Scope::set_operator_statistics`] is enabled
+	pub operator_calls: BTreeMap<ArcStr, u64>,
+	/// The deepest nesting of Egg function calls observed
+	pub peak_call_depth: usize,
+	/// Objects created using [`Scope::create_object`]
+	pub objects_allocated: u64,
+}
+
+impl Scope {
+	/// Statistics collected since the scope was created or last [`taken`](Scope::take_statistics)
+	pub fn statistics(&self) -> &Statistics {
+		&self.extras().statistics
+	}
+
+	/// Return the collected statistics, resetting them
+	pub fn take_statistics(&mut self) -> Statistics {
+		core::mem::take(&mut self.extras_mut().statistics)
+	}
+
+	/// Count calls to each operator in [`Statistics::operator_calls`]. Off by default, as it costs a map lookup on every builtin call
+	pub fn set_operator_statistics(&mut self, enabled: bool) {
+		self.extras_mut().operator_statistics = enabled;
+	}
+
+	#[inline]
+	pub(crate) fn record_operator_call(&mut self, name: &ArcStr) {
+		let extras = self.extras_mut();
+		if !extras.operator_statistics {
+			return;
+		}
+
+		let calls = &mut extras.statistics.operator_calls;
+
+		match calls.get_mut(name) {
+			Some(count) => *count += 1,
+			None => {
+				calls.insert(name.clone(), 1);
+			}
+		}
+	}
+
+	/// Enter an Egg function, returning the new call depth
+	pub(crate) fn enter_function(&mut self) -> usize {
+		let extras = self.extras_mut();
+		extras.call_depth += 1;
+
+		let statistics = &mut extras.statistics;
+		statistics.function_calls += 1;
+		statistics.peak_call_depth = statistics.peak_call_depth.max(extras.call_depth);
+
+		extras.call_depth
+	}
+
+	pub(crate) fn exit_function(&mut self) {
+		let extras = self.extras_mut();
+		extras.call_depth = extras.call_depth.saturating_sub(1);
+	}
+}
@@ -0,0 +1,241 @@
+use alloc::{
+	boxed::Box,
+	collections::btree_map::BTreeMap,
+	string::{String, ToString},
+	vec::Vec,
+};
+use core::{fmt, mem::take, ops::Range};
+use logos::Logos;
+
+use crate::{
+	error::{EggError, EggResult},
+	expression::Expression,
+	operators::Operator,
+	parser::{self, Token},
+};
+
+/// Insignificant source text: whitespace, comments or commas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trivia<'a> {
+	/// One of [`Token::WhiteSpace`], [`Token::Comment`] or [`Token::Comma`]
+	pub kind: Token,
+	pub text: &'a str,
+	pub span: Range<usize>,
+}
+
+/// A significant token, with the trivia preceding it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxToken<'a> {
+	pub kind: Token,
+	pub text: &'a str,
+	pub span: Range<usize>,
+	/// Trivia between the previous token and this one
+	pub leading: Vec<Trivia<'a>>,
+}
+
+/// A node in a [`SyntaxTree`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxNode<'a> {
+	/// A literal or a word
+	Atom(SyntaxToken<'a>),
+	/// A function call: `name(arguments...)`
+	Call {
+		name: SyntaxToken<'a>,
+		open: SyntaxToken<'a>,
+		arguments: Vec<SyntaxNode<'a>>,
+		/// The closing bracket, it's leading trivia follows the last argument
+		close: SyntaxToken<'a>,
+	},
+}
+
+impl SyntaxNode<'_> {
+	/// Span of the node, excluding it's leading trivia
+	pub fn span(&self) -> Range<usize> {
+		match self {
+			SyntaxNode::Atom(token) => token.span.clone(),
+			SyntaxNode::Call { name, close, .. } => name.span.start..close.span.end,
+		}
+	}
+
+	/// Trivia preceding the node
+	pub fn leading(&self) -> &[Trivia<'_>] {
+		match self {
+			SyntaxNode::Atom(token) | SyntaxNode::Call { name: token, .. } => &token.leading,
+		}
+	}
+
+	/// Documentation from `##` comments on the lines directly above the node, with the `##` removed.
+	///
+	/// ```
+	/// use egglang::syntax;
+	///
+	/// let script = "## Adds one\n## to a number\ndefine(plusOne, fn(n, sum(n, 1)))";
+	/// let tree = syntax::parse(script).unwrap();
+	///
+	/// assert_eq!(tree.nodes[0].doc_comment().unwrap(), "Adds one\nto a number");
+	/// ```
+	pub fn doc_comment(&self) -> Option<String> {
+		let leading = self.leading();
+		let mut lines = Vec::new();
+
+		for (idx, trivia) in leading.iter().enumerate().rev() {
+			match trivia.kind {
+				// A blank line separates the comments from the node
+				Token::WhiteSpace if trivia.text.matches('\n').count() > 1 => break,
+				Token::Comment if trivia.text.starts_with("##") => {
+					// Comments following a token on the same line belong to that token
+					let own_line = match idx.checked_sub(1).map(|idx| &leading[idx]) {
+						Some(previous) => previous.kind == Token::WhiteSpace && previous.text.contains('\n'),
+						None => trivia.span.start == 0,
+					};
+					if !own_line {
+						break;
+					}
+
+					let line = trivia.text.trim_start_matches('#').trim_end();
+					lines.push(line.strip_prefix(' ').unwrap_or(line));
+				}
+				Token::Comment => break,
+				_ => {}
+			}
+		}
+
+		if lines.is_empty() {
+			return None;
+		}
+
+		lines.reverse();
+		Some(lines.join("\n"))
+	}
+
+	/// Convert the node into an [`Expression`], identical to the one produced by [`parse`](crate::parser::parse)
+	pub fn lower(&self, operators: &BTreeMap<&str, Box<dyn Operator>>) -> Expression {
+		match self {
+			SyntaxNode::Atom(token) => parser::atom(&token.kind, token.text, token.span.clone()),
+			SyntaxNode::Call { name, arguments, .. } => {
+				let parameters = arguments.iter().map(|argument| argument.lower(operators)).collect();
+				parser::call(name.text.into(), parameters, self.span(), operators)
+			}
+		}
+	}
+}
+
+/// A lossless syntax tree of an Egg script. Comments, whitespace and commas are attached to the following token as trivia,
+/// so displaying the tree reproduces the source byte for byte.
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::syntax;
+///
+/// let script = "# Greet the user\nprintln(\"Hello\", name) # trailing\n";
+/// let tree = syntax::parse(script).unwrap();
+///
+/// assert_eq!(tree.to_string(), script);
+/// assert_eq!(tree.nodes[0].leading()[0].text, "# Greet the user");
+///
+/// // Lower into Expressions for evaluation
+/// let mut operators = operators::empty();
+/// operators::minimal(&mut operators);
+/// let expressions = tree.lower(&operators);
+/// assert_eq!(format!("{expressions:?}"), format!("{:?}", parse(script, &operators).unwrap()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxTree<'a> {
+	/// Top-level expressions
+	pub nodes: Vec<SyntaxNode<'a>>,
+	/// Trivia after the last expression
+	pub trailing: Vec<Trivia<'a>>,
+}
+
+impl SyntaxTree<'_> {
+	/// Convert the tree into [`Expressions`](Expression), identical to those produced by [`parse`](crate::parser::parse)
+	pub fn lower(&self, operators: &BTreeMap<&str, Box<dyn Operator>>) -> Vec<Expression> {
+		self.nodes.iter().map(|node| node.lower(operators)).collect()
+	}
+}
+
+/// Parses `source` into a lossless [`SyntaxTree`]
+pub fn parse(source: &str) -> EggResult<SyntaxTree<'_>> {
+	let mut nodes = Vec::new();
+	// Calls with open brackets, and their arguments so far
+	let mut stack: Vec<(SyntaxToken, SyntaxToken, Vec<SyntaxNode>)> = Vec::new();
+	let mut trivia = Vec::new();
+
+	for (token, span) in Token::lexer(source).spanned() {
+		let kind = token.map_err(|_| EggError::UnknownToken(source[span.clone()].to_string(), span.clone()))?;
+		let text = &source[span.clone()];
+
+		if kind.is_trivia() {
+			trivia.push(Trivia { kind, text, span });
+			continue;
+		}
+
+		let token = SyntaxToken {
+			kind,
+			text,
+			span: span.clone(),
+			leading: take(&mut trivia),
+		};
+		let current = match stack.last_mut() {
+			Some((.., arguments)) => arguments,
+			None => &mut nodes,
+		};
+
+		match kind {
+			Token::LeftBracket => match current.pop() {
+				Some(SyntaxNode::Atom(name)) if name.kind == Token::Word => stack.push((name, token, Vec::new())),
+				_ => return Err(EggError::ParserError(span, "Cannot use non-word as operation name".into())),
+			},
+			Token::RightBracket => {
+				let (name, open, arguments) = stack.pop().ok_or(EggError::UnbalancedBrackets(span.start))?;
+				let call = SyntaxNode::Call { name, open, arguments, close: token };
+
+				match stack.last_mut() {
+					Some((.., arguments)) => arguments.push(call),
+					None => nodes.push(call),
+				}
+			}
+			_ => current.push(SyntaxNode::Atom(token)),
+		}
+	}
+
+	// Brackets left open at the end of the script
+	if let Some((_, open, _)) = stack.first() {
+		return Err(EggError::UnbalancedBrackets(open.span.start));
+	}
+
+	Ok(SyntaxTree { nodes, trailing: trivia })
+}
+
+impl fmt::Display for Trivia<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.text)
+	}
+}
+
+impl fmt::Display for SyntaxToken<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.leading.iter().try_for_each(|trivia| write!(f, "{trivia}"))?;
+		f.write_str(self.text)
+	}
+}
+
+impl fmt::Display for SyntaxNode<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			SyntaxNode::Atom(token) => write!(f, "{token}"),
+			SyntaxNode::Call { name, open, arguments, close } => {
+				write!(f, "{name}{open}")?;
+				arguments.iter().try_for_each(|argument| write!(f, "{argument}"))?;
+				write!(f, "{close}")
+			}
+		}
+	}
+}
+
+impl fmt::Display for SyntaxTree<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.nodes.iter().try_for_each(|node| write!(f, "{node}"))?;
+		self.trailing.iter().try_for_each(|trivia| write!(f, "{trivia}"))
+	}
+}
@@ -0,0 +1,178 @@
+use alloc::{boxed::Box, collections::btree_map::BTreeMap, format, string::String, vec::Vec};
+use core::{fmt, ops::Range};
+
+use crate::{
+	error::{EggError, EggResult},
+	evaluator::evaluate,
+	expression::{Expression, Function, Value},
+	io::{InputBuffer, OutputBuffer},
+	operators::Operator,
+	parser,
+	scope::Scope,
+};
+
+/// Why a test failed
+#[derive(Debug, Clone)]
+pub enum Failure {
+	/// The test raised an error, eg: a failing `assert`
+	Error(EggError),
+	/// The script printed something other than the expected output
+	Output { expected: String },
+}
+
+impl fmt::Display for Failure {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Failure::Error(error) => write!(f, "{error}"),
+			Failure::Output { .. } => f.write_str("Printed output differs from the expected output"),
+		}
+	}
+}
+
+/// The outcome of a single test, see [`TestRunner::run`]
+#[derive(Debug, Clone)]
+pub struct TestResult {
+	pub name: String,
+	/// Span of the test in the script
+	pub span: Range<usize>,
+	/// Everything printed while running the test, including by the script's setup
+	pub output: String,
+	pub failure: Option<Failure>,
+}
+
+impl TestResult {
+	pub fn passed(&self) -> bool {
+		self.failure.is_none()
+	}
+}
+
+/// Runs tests written in Egg.
+///
+/// Tests are either `test("name", body...)` blocks, or functions defined at the top level with a name starting with `test_`.
+/// Each test runs in a fresh [`Scope`], after evaluating the rest of the script's top level expressions as setup.
+///
+/// Output of `print` and `println` is captured in an [`OutputBuffer`] rather than written to stdout, and `readline` reads from an empty [`InputBuffer`].
+/// When the expected output of a script is known, the script is also run once without it's tests, and it's output compared.
+///
+/// ```
+/// use egglang::prelude::*;
+/// use egglang::testing::TestRunner;
+///
+/// let mut operators = operators::empty();
+/// operators::full(&mut operators);
+///
+/// let script = r#"
+/// define(double, fn(n, multiply(n, 2)))
+/// define(test_double, fn(assert(equals(double(2), 4), "2 doubled is 4")))
+/// test("doubling zero", assert(equals(double(0), 1), "0 doubled is 1"))
+/// println("Hello")
+/// "#;
+///
+/// let results = TestRunner::new(operators).run(script, Some("Hello\n")).unwrap();
+/// let outcomes = results.iter().map(|r| (r.name.as_str(), r.passed())).collect::<Vec<_>>();
+/// assert_eq!(outcomes, [("test_double", true), ("doubling zero", false), ("expected output", true)]);
+/// assert_eq!(results[1].failure.as_ref().unwrap().to_string(), "Assertion failed: \"0 doubled is 1\"");
+/// ```
+pub struct TestRunner {
+	operators: BTreeMap<&'static str, Box<dyn Operator>>,
+}
+
+impl TestRunner {
+	/// Run tests with the given builtins
+	pub fn new(operators: BTreeMap<&'static str, Box<dyn Operator>>) -> TestRunner {
+		TestRunner { operators }
+	}
+
+	/// Run the tests in a script, in the order they are written. Fails only if the script cannot be parsed.
+	pub fn run(&self, source: &str, expected: Option<&str>) -> EggResult<Vec<TestResult>> {
+		let expressions = parser::parse(source, &self.operators)?;
+		// Test blocks are only run as tests
+		let setup = expressions.iter().filter(|expr| test_block(expr).is_none()).collect::<Vec<_>>();
+
+		let mut results = Vec::new();
+		for expr in &expressions {
+			let (name, body) = match (test_block(expr), test_function(expr)) {
+				(Some((name, body)), _) => (name, body.to_vec()),
+				// Call the function without arguments
+				(_, Some(name)) => {
+					let call = Expression::FnCall {
+						function: Function::Script(name.as_str().into()),
+						parameters: Vec::new(),
+						span: expr.span(),
+					};
+					(name, alloc::vec![call])
+				}
+				_ => continue,
+			};
+
+			let (output, result) = self.execute(setup.iter().copied().chain(&body));
+			results.push(TestResult {
+				name,
+				span: expr.span(),
+				output,
+				failure: result.err().map(Failure::Error),
+			});
+		}
+
+		if let Some(expected) = expected {
+			let (output, result) = self.execute(setup.iter().copied());
+			let failure = match result {
+				Err(error) => Some(Failure::Error(error)),
+				Ok(_) if output != expected => Some(Failure::Output { expected: expected.into() }),
+				Ok(_) => None,
+			};
+
+			results.push(TestResult {
+				name: "expected output".into(),
+				span: 0..source.len(),
+				output,
+				failure,
+			});
+		}
+
+		Ok(results)
+	}
+
+	/// Evaluate expressions in a fresh scope, capturing their output
+	fn execute<'a>(&self, expressions: impl Iterator<Item = &'a Expression>) -> (String, EggResult<()>) {
+		let output = OutputBuffer::default();
+		let mut scope = Scope::default();
+		scope.set_output(Box::new(output.clone()));
+		scope.set_input(Box::new(InputBuffer::default()));
+
+		let result = match expressions.into_iter().try_for_each(|expr| evaluate(expr, &mut scope).map(drop)) {
+			// Exiting successfully ends the test early
+			Err(EggError::Exit(0)) => Ok(()),
+			result => result,
+		};
+		(output.take(), result)
+	}
+}
+
+/// The name and body of a `test("name", body...)` block
+fn test_block(expr: &Expression) -> Option<(String, &[Expression])> {
+	let Expression::FnCall { function, parameters, .. } = expr else { return None };
+	let (Function::Script(name) | Function::Host(name, _)) = function;
+
+	match parameters.as_slice() {
+		[Expression::Value { value: Value::String(test), .. }, body @ ..] if name == "test" => Some((test.as_str().into(), body)),
+		[test, body @ ..] if name == "test" => Some((format!("{test}"), body)),
+		_ => None,
+	}
+}
+
+/// The name of a top level function definition starting with `test_`
+fn test_function(expr: &Expression) -> Option<String> {
+	let Expression::FnCall { function, parameters, .. } = expr else { return None };
+	let (Function::Script(define) | Function::Host(define, _)) = function;
+
+	match parameters.as_slice() {
+		[
+			Expression::Word { name, .. },
+			Expression::FnCall {
+				function: Function::Host(create, _), ..
+			},
+		] if define == "define" && create == "fn" && name.starts_with("test_") => Some(name.as_str().into()),
+		_ => None,
+	}
+}
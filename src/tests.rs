@@ -1,5 +1,29 @@
-use crate::{evaluator, operators, parser};
-use std::fs::{read_dir, read_to_string};
+use crate::{
+	debugger::{Debugger, Frontend, Paused, Step},
+	docs,
+	error::{EggError, EggResult, FileSystemError},
+	evaluator,
+	expression::{Expression, Function, Value, Visitor, VisitorMut, walk_call_mut},
+	formatter::{self, FormatOptions},
+	hooks::Hook,
+	io,
+	lint::{Linter, Rule},
+	operators,
+	optimizer::optimize,
+	parser::{self, Token},
+	precompiled,
+	scope::{CancellationToken, Limits, Scope},
+	syntax::{self, SyntaxNode},
+	testing::{Failure, TestRunner},
+};
+use arcstr::ArcStr;
+use std::{
+	cell::RefCell,
+	fs::{read_dir, read_to_string},
+	ops::Range,
+	rc::Rc,
+	time::Duration,
+};
 
 #[test]
 fn test() {
@@ -13,12 +37,12 @@ fn test() {
 			.unwrap()
 			.filter_map(|entry| entry.ok())
 			.filter(|entry| entry.file_type().ok().map(|t| t.is_file()).unwrap_or(false))
-			.filter(|e| e.path().ends_with(".egg"))
+			.filter(|entry| entry.path().extension().is_some_and(|ext| ext == "egg"))
 			.filter_map(|entry| read_to_string(entry.path()).ok().map(|s| (entry.path(), s)))
 	};
 
 	scripts.for_each(|(path, script)| {
-		let mut scope = Default::default();
+		let mut scope = Scope::default();
 
 		println!("\n[{}]", path.display());
 		let ast = parser::parse(script, &operators).unwrap();
@@ -26,7 +50,939 @@ fn test() {
 		ast.iter().for_each(|expr| {
 			evaluator::evaluate(expr, &mut scope).unwrap();
 		});
+
+		println!("\nCalls to evaluate(...): {}", scope.statistics().evaluations);
+	});
+}
+
+/// Compares the output of each script with `<script>.expected`, set `EGG_UPDATE_EXPECTED` to rewrite them instead
+#[test]
+fn expected_output() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+
+	let mut scripts = read_dir("scripts")
+		.unwrap()
+		.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+		.filter(|path| path.extension().is_some_and(|ext| ext == "egg"))
+		.collect::<Vec<_>>();
+	scripts.sort();
+
+	for path in scripts {
+		let source = read_to_string(&path).unwrap();
+		let captured = io::capture(&source, &operators, "");
+		assert!(captured.result.is_ok(), "{} failed: {:?}", path.display(), captured.result);
+
+		let expected = path.with_extension("expected");
+		if std::env::var_os("EGG_UPDATE_EXPECTED").is_some() {
+			std::fs::write(&expected, &captured.output).unwrap();
+			continue;
+		}
+
+		let Ok(expected) = read_to_string(&expected) else {
+			panic!("{} is missing, run with EGG_UPDATE_EXPECTED=1 to create it", expected.display());
+		};
+		assert_eq!(captured.output, expected, "Output of {} differs", path.display());
+	}
+
+	// Errors are captured along with the output before them
+	let captured = io::capture("println(\"partial\")\nundefined()", &operators, "");
+	assert_eq!(captured.output, "partial\n");
+	assert!(matches!(captured.result, Err(EggError::FunctionNotFound(_))));
+	assert!(matches!(io::capture("println(", &operators, "").result, Err(EggError::UnbalancedBrackets(_))));
+}
+
+#[test]
+fn statistics() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+
+	let script = "define(fact, fn(n, if(less_than(n, 2), 1, multiply(n, fact(subtract(n, 1))))))  define(o, object.new())  fact(5)";
+	let ast = parser::parse(script, &operators).unwrap();
+
+	let mut scope = Scope::default();
+	ast.iter().for_each(|expr| drop(evaluator::evaluate(expr, &mut scope).unwrap()));
+
+	// Operator calls are only counted on request
+	let statistics = scope.take_statistics();
+	assert_eq!(statistics.function_calls, 5);
+	assert!(statistics.operator_calls.is_empty());
+
+	let mut scope = Scope::default();
+	scope.set_operator_statistics(true);
+	ast.iter().for_each(|expr| drop(evaluator::evaluate(expr, &mut scope).unwrap()));
+
+	let statistics = scope.take_statistics();
+	assert_eq!(statistics.function_calls, 5);
+	assert_eq!(statistics.peak_call_depth, 5);
+	assert_eq!(statistics.objects_allocated, 1);
+	assert_eq!(statistics.operator_calls["multiply"], 4);
+	assert_eq!(statistics.operator_calls["define"], 2);
+
+	assert_eq!(scope.statistics().evaluations, 0);
+}
+
+#[test]
+fn memory_limits() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+
+	let mut scope = Scope::default();
+	scope.set_limits(Limits {
+		object_entries: Some(2),
+		live_values: Some(2),
+		..Default::default()
+	});
+
+	let script = "define(a, object.new()) object.insert(a, 1, 1) object.insert(a, 2, 2) object.insert(a, 2, 3) object.insert(a, 3, 3)";
+	let ast = parser::parse(script, &operators).unwrap();
+	let results = ast.iter().map(|expr| evaluator::evaluate(expr, &mut scope)).collect::<Vec<_>>();
+
+	assert!(results[..4].iter().all(|r| r.is_ok()));
+	assert!(matches!(results[4], Err(EggError::MemoryLimitExceeded(_))));
+
+	// Deleting an object releases it's entries and slot
+	let ast = parser::parse("delete(a) define(b, object.new()) define(c, fn(Nil)) define(d, fn(Nil))", &operators).unwrap();
+	let results = ast.iter().map(|expr| evaluator::evaluate(expr, &mut scope)).collect::<Vec<_>>();
+
+	assert!(results[..3].iter().all(|r| r.is_ok()));
+	assert!(matches!(results[3], Err(EggError::MemoryLimitExceeded(_))));
+	assert_eq!(scope.usage().object_entries, 0);
+	assert_eq!(scope.usage().live_values, 2);
+
+	// Only strings that are still held count against the limit
+	let mut scope = Scope::default();
+	scope.set_limits(Limits {
+		string_bytes: Some(32),
+		..Default::default()
 	});
 
-	println!("\nCalls to evaluate(...): {}", unsafe { evaluator::EVALUATIONS });
+	let script = r#"define(x, "") repeat(1000, set(x, string.concat("Hello", ", World"))) define(o, object.new()) object.insert(o, "key", x)"#;
+	let ast = parser::parse(script, &operators).unwrap();
+	assert!(ast.iter().all(|expr| evaluator::evaluate(expr, &mut scope).is_ok()));
+	assert_eq!(scope.usage().string_bytes, 27);
+
+	let ast = parser::parse(r#"string.concat(x, x)"#, &operators).unwrap();
+	assert!(matches!(evaluator::evaluate(&ast[0], &mut scope), Err(EggError::MemoryLimitExceeded(_))));
+
+	let ast = parser::parse(r#"object.remove(o, "key") delete(x)"#, &operators).unwrap();
+	assert!(ast.iter().all(|expr| evaluator::evaluate(expr, &mut scope).is_ok()));
+	assert_eq!(scope.usage().string_bytes, 0);
+}
+
+#[test]
+fn cancellation_and_timeouts() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+
+	// Deadlines interrupt infinite loops
+	let mut scope = Scope::default();
+	scope.set_timeout(Duration::from_millis(20));
+
+	let ast = parser::parse("while(true, sum(1 2))", &operators).unwrap();
+	assert!(matches!(evaluator::evaluate(&ast[0], &mut scope), Err(EggError::Timeout)));
+
+	// Tokens can be cancelled from another thread, including during sleep(...)
+	let mut scope = Scope::default();
+	let token = CancellationToken::new();
+	scope.set_cancellation_token(Some(token.clone()));
+
+	let handle = std::thread::spawn(move || {
+		std::thread::sleep(Duration::from_millis(20));
+		token.cancel();
+	});
+
+	let ast = parser::parse("sleep(60000)", &operators).unwrap();
+	assert!(matches!(evaluator::evaluate(&ast[0], &mut scope), Err(EggError::Cancelled)));
+	handle.join().unwrap();
+}
+
+#[test]
+fn deep_recursion() {
+	// Work done on every evaluation costs the same regardless of how deep in the call stack it happens
+	fn elapsed(depth: usize, iterations: usize) -> Duration {
+		let mut operators = operators::empty();
+		operators::minimal(&mut operators);
+
+		let script = format!("define(deep, fn(n, if(greater_than(n, 0), deep(sum(n, -1)), repeat({iterations}, sum(1, 1)))))\ndeep({depth})");
+		let ast = parser::parse(script, &operators).unwrap();
+
+		let mut scope = Scope::default();
+		let start = std::time::Instant::now();
+		ast.iter().for_each(|expr| drop(evaluator::evaluate(expr, &mut scope).unwrap()));
+		start.elapsed()
+	}
+
+	// Reaching the bottom of the stack is timed separately, as looking `deep` up walks every frame
+	let handle = std::thread::Builder::new()
+		.stack_size(256 << 20)
+		.spawn(|| (elapsed(1, 20000), elapsed(1000, 20000).saturating_sub(elapsed(1000, 0))))
+		.unwrap();
+	let (shallow, deep) = handle.join().unwrap();
+	assert!(deep < shallow * 10, "took {deep:?} at a depth of 1000, against {shallow:?} at a depth of 1");
+}
+
+#[test]
+fn profiling() {
+	let mut operators = operators::empty();
+	operators::minimal(&mut operators);
+
+	let script = "define(count, fn(n, if(greater_than(n, 0), count(sum(n, -1)), n)))\ncount(2)";
+	let ast = parser::parse(script, &operators).unwrap();
+
+	let mut scope = Scope::default();
+	scope.start_profiling();
+	ast.iter().for_each(|expr| drop(evaluator::evaluate(expr, &mut scope).unwrap()));
+	let profile = scope.stop_profiling().unwrap();
+
+	// Every call is counted, recursive ones included
+	let calls = profile.entries.iter().map(|(key, entry)| (key.name(), entry.calls)).collect::<Vec<_>>();
+	assert_eq!(calls, [("count", 3), ("define", 1), ("fn", 1), ("greater_than", 3), ("if", 3), ("sum", 2)]);
+	for entry in profile.entries.values() {
+		assert!(entry.inclusive >= entry.exclusive);
+	}
+
+	// Each unique stack is collapsed onto a single line
+	let collapsed = profile.collapsed();
+	let stacks = collapsed.lines().map(|line| line.rsplit_once(' ').unwrap().0).collect::<Vec<_>>();
+	assert_eq!(
+		stacks,
+		[
+			"count",
+			"count;if",
+			"count;if;count",
+			"count;if;count;if",
+			"count;if;count;if;count",
+			"count;if;count;if;count;if",
+			"count;if;count;if;count;if;greater_than",
+			"count;if;count;if;greater_than",
+			"count;if;count;if;sum",
+			"count;if;greater_than",
+			"count;if;sum",
+			"define",
+			"define;fn"
+		]
+	);
+
+	// Arguments are evaluated by the caller, before the function is entered
+	let script = "define(f, fn(x, x))\ndefine(g, fn(x, x))\nf(g(1))";
+	let ast = parser::parse(script, &operators).unwrap();
+
+	let mut scope = Scope::default();
+	scope.start_profiling();
+	ast.iter().for_each(|expr| drop(evaluator::evaluate(expr, &mut scope).unwrap()));
+
+	let collapsed = scope.stop_profiling().unwrap().collapsed();
+	let stacks = collapsed.lines().map(|line| line.rsplit_once(' ').unwrap().0).collect::<Vec<_>>();
+	assert_eq!(stacks, ["define", "define;fn", "f", "g"]);
+}
+
+#[test]
+fn hooks() {
+	#[derive(Default)]
+	struct Recorder(Rc<RefCell<Vec<String>>>);
+
+	impl Hook for Recorder {
+		fn on_call(&mut self, name: &str, arguments: &[Value], _: &mut Scope) {
+			self.0.borrow_mut().push(format!("call {name} {arguments:?}"));
+		}
+
+		fn on_return(&mut self, name: &str, result: &EggResult<Value>, _: &mut Scope) {
+			self.0.borrow_mut().push(format!("return {name} {:?}", result.as_ref().ok()));
+		}
+
+		fn on_set(&mut self, name: &str, value: &Value, _: &mut Scope) {
+			self.0.borrow_mut().push(format!("set {name} {value}"));
+		}
+
+		fn on_delete(&mut self, name: &str, _: &mut Scope) {
+			self.0.borrow_mut().push(format!("delete {name}"));
+		}
+
+		fn on_error(&mut self, error: &EggError, expr: &Expression, _: &mut Scope) {
+			self.0.borrow_mut().push(format!("error {error} @ {:?}", expr.span()));
+		}
+	}
+
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+
+	let script = "define(x, 1) define(add, fn(a, b, sum(a b))) set(x, add(x, 2)) delete(x) add(y, 2)";
+	let ast = parser::parse(script, &operators).unwrap();
+
+	let events = Rc::new(RefCell::new(Vec::new()));
+	let mut scope = Scope::default();
+	scope.add_hook(Box::new(Recorder(events.clone())));
+	ast.iter().for_each(|expr| drop(evaluator::evaluate(expr, &mut scope)));
+
+	assert_eq!(
+		*events.borrow(),
+		[
+			"call add [1.0, 2.0]",
+			"return add Some(3.0)",
+			"set x 3",
+			"delete x",
+			"error Binding not found in current scope: y @ 77..78"
+		]
+	);
+}
+
+#[test]
+fn debugger_stepping() {
+	struct Script(Vec<Step>, Rc<RefCell<Vec<(usize, usize, usize)>>>);
+
+	impl Frontend for Script {
+		fn paused(&mut self, paused: &mut Paused<'_>) -> Step {
+			self.1.borrow_mut().push((paused.line, paused.column, paused.stack.len()));
+			self.0.pop().unwrap_or(Step::Continue)
+		}
+	}
+
+	let mut operators = operators::empty();
+	operators::minimal(&mut operators);
+
+	let script = "define(f, fn(x, do(\n  sum(x 1),\n  multiply(x 2)\n)))\nf(1)\nf(2)";
+	let ast = parser::parse(script, &operators).unwrap();
+
+	// Steps are popped from the back
+	let steps = vec![Step::StepOver, Step::StepOut, Step::StepOver, Step::StepIn, Step::StepIn, Step::StepOver];
+	let pauses = Rc::new(RefCell::new(Vec::new()));
+
+	let mut debugger = Debugger::new(script, Script(steps, pauses.clone()));
+	debugger.stop_on_entry(true);
+
+	let mut scope = Scope::default();
+	scope.add_hook(Box::new(debugger));
+	ast.iter().for_each(|expr| drop(evaluator::evaluate(expr, &mut scope).unwrap()));
+
+	assert_eq!(*pauses.borrow(), [(1, 1, 1), (5, 1, 1), (1, 17, 2), (2, 3, 2), (3, 3, 2), (6, 1, 1)]);
+}
+
+#[test]
+fn parse_errors() {
+	let mut operators = operators::empty();
+	operators::minimal(&mut operators);
+
+	// Brackets left open at the end of the script are reported at the outermost one
+	assert!(matches!(parser::parse("sum(1, 2", &operators), Err(EggError::UnbalancedBrackets(3))));
+	assert!(matches!(parser::parse("do(sum(1, 2)\nsum(3", &operators), Err(EggError::UnbalancedBrackets(2))));
+	assert!(matches!(parser::parse("sum(1, 2))", &operators), Err(EggError::UnbalancedBrackets(9))));
+
+	// Unknown tokens carry their location
+	let error = parser::parse("define(x, 5)\n@", &operators).unwrap_err();
+	assert!(matches!(&error, EggError::UnknownToken(token, span) if token == "@" && *span == (13..14)));
+	assert_eq!(error.span(), Some(13..14));
+}
+
+#[test]
+fn editor_support() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+
+	// Every builtin is documented, starting with it's signature
+	for (name, operator) in &operators {
+		let documentation = operator.documentation().unwrap_or_else(|| panic!("{name} is undocumented"));
+		assert!(documentation.starts_with(name), "{name}: {documentation}");
+	}
+
+	// Parse errors carry the location of the offending source
+	let source = "define(x, 5)\nsum(x 1";
+	let error = parser::parse(source, &operators).unwrap_err();
+	assert_eq!(error.span(), Some(16..17));
+
+	let index = parser::LineIndex::new(source);
+	assert_eq!(index.location(16), (2, 4));
+	assert_eq!(index.offset(2, 4), Some(16));
+}
+
+#[test]
+fn formatting() {
+	let options = FormatOptions::default();
+
+	// Separators and spacing are normalised, while formatted scripts are left untouched
+	let script = "set(x,multiply(-10 x 2E2))\nrepeat(20,set(x,sum(3.5 1E-3 x)))";
+	assert_eq!(formatter::format(script, &options).unwrap(), "set(x, multiply(-10, x, 2E2))\nrepeat(20, set(x, sum(3.5, 1E-3, x)))\n");
+
+	let script = "define(x, 5) # five\n\nrepeat(3, println(\"x is \", x))\n";
+	assert_eq!(formatter::format(script, &options).unwrap(), script);
+
+	// Comments are preserved wherever they appear
+	let script = "\n# header\ndo( # first\n  a,   # after a\n\n\n  b\n  # dangling\n)\nx # end\n\n\n# eof\n";
+	let formatted = formatter::format(script, &options).unwrap();
+	assert_eq!(formatted, "# header\ndo(\n\t# first\n\ta, # after a\n\n\tb\n\t# dangling\n)\nx # end\n\n# eof\n");
+	assert_eq!(formatter::format(&formatted, &options).unwrap(), formatted);
+
+	// Long calls are broken across lines
+	let options = FormatOptions { max_width: 20, ..options };
+	assert_eq!(formatter::format("println(\"Hello\", \"World\")", &options).unwrap(), "println(\n\t\"Hello\",\n\t\"World\"\n)\n");
+
+	assert!(matches!(formatter::format("sum(1 2", &options), Err(EggError::UnbalancedBrackets(3))));
+}
+
+#[test]
+fn syntax_tree() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+
+	for entry in read_dir("scripts").unwrap().filter_map(|entry| entry.ok()) {
+		let path = entry.path();
+		if path.extension().is_some_and(|ext| ext == "egg") {
+			let script = read_to_string(&path).unwrap();
+			let tree = syntax::parse(&script).unwrap();
+
+			// Round trips byte for byte, and lowers to the same expressions as the parser
+			assert_eq!(tree.to_string(), script);
+			assert_eq!(format!("{:?}", tree.lower(&operators)), format!("{:?}", parser::parse(&script, &operators).unwrap()));
+		}
+	}
+
+	let tree = syntax::parse("do( # first\n\ta,\tb # last\n)\n# end").unwrap();
+	let SyntaxNode::Call { open, arguments, close, .. } = &tree.nodes[0] else {
+		panic!("Expected a call")
+	};
+	assert_eq!(open.text, "(");
+	assert_eq!(arguments[1].leading().iter().map(|t| t.kind).collect::<Vec<_>>(), [Token::Comma, Token::WhiteSpace]);
+	assert_eq!(close.leading[1].text, "# last");
+	assert_eq!(tree.trailing.last().unwrap().text, "# end");
+
+	assert!(matches!(syntax::parse("sum(1 2"), Err(EggError::UnbalancedBrackets(3))));
+	assert!(matches!(syntax::parse("5(1 2)"), Err(EggError::ParserError(..))));
+}
+
+#[test]
+fn unparsing() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+
+	// Rendered scripts parse back into the same expressions
+	for entry in read_dir("scripts").unwrap().filter_map(|entry| entry.ok()) {
+		let path = entry.path();
+		if path.extension().is_some_and(|ext| ext == "egg") {
+			let expressions = parser::parse(read_to_string(&path).unwrap(), &operators).unwrap();
+			let source = expressions.iter().map(|expr| expr.to_string()).collect::<Vec<_>>().join("\n");
+
+			let reparsed = parser::parse(&source, &operators).unwrap();
+			assert_eq!(reparsed.iter().map(|expr| expr.to_string()).collect::<Vec<_>>().join("\n"), source);
+		}
+	}
+
+	// Values without literals are rendered as expressions producing them
+	let mut call = parser::parse("do()", &operators).unwrap().remove(0);
+	if let Expression::FnCall { parameters, .. } = &mut call {
+		let values = [Value::Nil, f32::INFINITY.into(), f32::NEG_INFINITY.into(), 1e-7.into(), (-2.5).into()];
+		parameters.extend(values.into_iter().map(|value| Expression::Value { value, span: 0..0 }));
+	}
+	assert_eq!(call.to_string(), "do(do(), divide(1, 0), divide(-1, 0), 0.0000001, -2.5)");
+
+	let nan = Expression::Value { value: f32::NAN.into(), span: 0..0 };
+	let mut scope = Scope::default();
+	let reparsed = parser::parse(nan.to_string(), &operators).unwrap();
+	assert!(matches!(evaluator::evaluate(&reparsed[0], &mut scope), Ok(Value::Number(n)) if n.is_nan()));
+}
+
+#[test]
+fn doc_comments() {
+	let script = "## File header\n\n## Adds one\n## to `n`\ndefine(plusOne, fn(n, sum(n, 1))) ## Not for x\ndefine(x, 5)\n## Hidden\n# by a comment\ndefine(y, 6)";
+	let definitions = docs::definitions(script).unwrap();
+
+	let documentation = definitions.iter().map(|d| (d.signature(), d.documentation.clone())).collect::<Vec<_>>();
+	assert_eq!(
+		documentation,
+		[("plusOne(n)".to_string(), Some("Adds one\nto `n`".to_string())), ("x".to_string(), None), ("y".to_string(), None)]
+	);
+
+	let markdown = docs::markdown("Library", &definitions[..2]);
+	assert_eq!(markdown, "# Library\n\n## Functions\n\n### `plusOne(n)`\n\nAdds one\nto `n`\n\n## Variables\n\n### `x`\n");
+
+	let html = docs::html("<Library>", &definitions[..1]);
+	assert!(html.contains("<title>&lt;Library&gt;</title>"));
+	assert!(html.contains("<section id=\"plusOne\">\n<h3><code>plusOne(n)</code></h3>\n<p>Adds one\nto `n`</p>\n</section>"));
+}
+
+#[test]
+fn linting() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+
+	let script = r#"
+define(counter, 0)
+define(increment, fn(amount, do(
+	define(next, sum(counter, amount)),
+	define(next, 0),
+	set(counter, next),
+	set(total, counter)
+)))
+define(_ignored, 1)
+define(unused, fn(callback, callback()))
+increment(1)
+missing(2)
+while(false, increment(1))
+if(1, println(counter), greet)
+"#;
+	let expressions = parser::parse(script, &operators).unwrap();
+
+	let mut linter = Linter::default();
+	let warnings = linter.lint(&expressions).into_iter().map(|w| (w.rule, script[w.span].to_string())).collect::<Vec<_>>();
+	assert_eq!(
+		warnings,
+		[
+			(Rule::Redefinition, "next".to_string()),
+			(Rule::UndefinedSet, "total".to_string()),
+			(Rule::UnusedVariable, "unused".to_string()),
+			(Rule::UndefinedFunction, "missing".to_string()),
+			(Rule::ConstantCondition, "false".to_string()),
+			(Rule::ConstantCondition, "1".to_string()),
+		]
+	);
+
+	// Rules can be disabled, and host provided names declared
+	linter.allow(Rule::ConstantCondition);
+	linter.allow(Rule::UnusedVariable);
+	linter.add_global("total");
+	linter.add_global("missing");
+	assert_eq!(linter.lint(&expressions).iter().map(|w| w.rule).collect::<Vec<_>>(), [Rule::Redefinition]);
+	assert_eq!(Rule::from_name("unused-variable"), Some(Rule::UnusedVariable));
+
+	// Defining a name in both branches of an if, or again after deleting it, is not a redefinition
+	let linter = Linter::default();
+	let script = "define(c, True)\nif(c, define(x, 1), define(x, 2))\ndefine(y, 1) delete(y) define(y, 2)\nprintln(x, y)";
+	assert_eq!(linter.lint(&parser::parse(script, &operators).unwrap()), []);
+
+	// Names defined in a branch remain defined after the if
+	let script = "define(c, True)\nif(c, define(x, 1), 0)\ndefine(x, 2)\nprintln(x)";
+	let warnings = linter.lint(&parser::parse(script, &operators).unwrap());
+	assert_eq!(warnings.iter().map(|w| (w.rule, &script[w.span.clone()])).collect::<Vec<_>>(), [(Rule::Redefinition, "x")]);
+}
+
+#[test]
+fn optimizing() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+
+	let optimized = |script: &str| optimize(parser::parse(script, &operators).unwrap()).iter().map(|expr| expr.to_string()).collect::<Vec<_>>();
+
+	// Pure builtins with literal arguments are folded
+	assert_eq!(optimized("multiply(12, 12, 12) string.concat(\"a\", str(5))"), ["1728", "\"a5\""]);
+	assert_eq!(optimized("sum(x, multiply(2, 3))"), ["sum(x, 6)"]);
+
+	// Dead branches are removed, unless the condition would error
+	assert_eq!(optimized("if(True, a, b) if(0, a, b) if(\"yes\", a, b)"), ["a", "b", "if(\"yes\", a, b)"]);
+
+	// Nested blocks are flattened, discarding unused literals
+	assert_eq!(optimized("do(1, do(println(x), do()), 2, x)"), ["do(println(x), x)"]);
+	assert_eq!(optimized("do(println(x), do())"), ["do(println(x), do())"]);
+	assert_eq!(optimized("do(do(5))"), ["5"]);
+
+	// Failing calls and impure calls are kept
+	assert_eq!(optimized("sum(1, \"a\") println(1)"), ["sum(1, \"a\")", "println(1)"]);
+
+	// Including calls with the wrong number of arguments, even where they can never run
+	assert_eq!(optimized("if(False, subtract(1), println(\"ok\")) string.to_upper()"), ["println(\"ok\")", "string.to_upper()"]);
+
+	// Optimized scripts yield the same results
+	let script = r#"
+define(total, 0)
+define(add, fn(n, do(set(total, sum(total, n, multiply(2, 3))), total)))
+repeat(3, do(do(add(1)), 5))
+if(greater_than(10, 2), add(total), add(1))
+"#;
+
+	let results = |expressions: Vec<Expression>| {
+		let mut scope = Scope::default();
+		expressions.iter().map(|expr| evaluator::evaluate(expr, &mut scope).unwrap()).collect::<Vec<_>>()
+	};
+
+	let expressions = parser::parse(script, &operators).unwrap();
+	assert_eq!(results(optimize(expressions.clone())), results(expressions));
+}
+
+#[test]
+fn visitors() {
+	let mut operators = operators::empty();
+	operators::minimal(&mut operators);
+
+	// Count the variables read by a script
+	#[derive(Default)]
+	struct Words(usize);
+
+	impl Visitor for Words {
+		fn visit_word(&mut self, _name: &ArcStr, _span: &Range<usize>) {
+			self.0 += 1;
+		}
+	}
+
+	// Rename calls to a script function
+	struct Rename;
+
+	impl VisitorMut for Rename {
+		fn visit_call_mut(&mut self, function: &mut Function, parameters: &mut Vec<Expression>, span: &mut Range<usize>) {
+			if let Function::Script(name) = function
+				&& name == "old"
+			{
+				*name = "new".into();
+			}
+			walk_call_mut(self, function, parameters, span);
+		}
+	}
+
+	let mut script = parser::parse("define(x, old(y, old(1))) sum(x, z)", &operators).unwrap();
+
+	let mut words = Words::default();
+	words.visit_expressions(&script);
+	assert_eq!(words.0, 4);
+
+	Rename.visit_expressions_mut(&mut script);
+	assert_eq!(script.iter().map(|expr| expr.to_string()).collect::<Vec<_>>(), ["define(x, new(y, new(1)))", "sum(x, z)"]);
+}
+
+#[test]
+fn precompiling() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+
+	let scripts = read_dir("scripts")
+		.unwrap()
+		.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+		.filter(|path| path.extension().is_some_and(|ext| ext == "egg"));
+
+	// Every script survives a round trip
+	for path in scripts {
+		let script = parser::parse(read_to_string(&path).unwrap(), &operators).unwrap();
+		let bytes = precompiled::to_bytes(&script).unwrap();
+
+		let loaded = precompiled::from_bytes(&bytes, &operators).unwrap();
+		assert_eq!(format!("{loaded:?}"), format!("{script:?}"), "{}", path.display());
+
+		#[cfg(feature = "json")]
+		{
+			let loaded = precompiled::from_json(&precompiled::to_json(&script).unwrap(), &operators).unwrap();
+			assert_eq!(format!("{loaded:?}"), format!("{script:?}"), "{}", path.display());
+		}
+	}
+
+	let script = parser::parse("println(sum(1, 2))", &operators).unwrap();
+	let bytes = precompiled::to_bytes(&script).unwrap();
+
+	// Corrupted or incompatible data is rejected
+	let invalid = |bytes: &[u8], operators| matches!(precompiled::from_bytes(bytes, operators), Err(EggError::InvalidPrecompiledScript(_)));
+	assert!(invalid(b"println(1)", &operators));
+	assert!(invalid(&[&bytes[..4], &[2, 0], &bytes[6..]].concat(), &operators));
+	assert!(invalid(&bytes[..bytes.len() - 1], &operators));
+	assert!(invalid(&[&bytes[..], &[0]].concat(), &operators));
+
+	// Builtins must be registered when loading
+	let mut minimal = operators::empty();
+	operators::minimal(&mut minimal);
+	let Err(EggError::InvalidPrecompiledScript(reason)) = precompiled::from_bytes(&bytes, &minimal) else {
+		panic!("Expected an error")
+	};
+	assert_eq!(reason, "Builtin `println` is not registered");
+
+	// Deeply nested calls are rejected instead of overflowing the stack
+	let nested = [&precompiled::MAGIC[..], &precompiled::VERSION.to_le_bytes(), &[1], &[6, 1, b'f', 1].repeat(100_000)].concat();
+	let Err(EggError::InvalidPrecompiledScript(reason)) = precompiled::from_bytes(&nested, &operators) else {
+		panic!("Expected an error")
+	};
+	assert!(reason.starts_with("Expressions nested deeper than"), "{reason}");
+
+	let depth = precompiled::MAX_DEPTH;
+	let script = parser::parse(format!("{}1{}", "f(".repeat(depth), ")".repeat(depth)), &operators).unwrap();
+	assert!(precompiled::from_bytes(&precompiled::to_bytes(&script).unwrap(), &operators).is_ok());
+
+	// Loaded scripts evaluate as if parsed
+	let script = parser::parse("define(x, 5) repeat(3, set(x, multiply(x, 2))) x", &operators).unwrap();
+	let loaded = precompiled::from_bytes(&precompiled::to_bytes(&script).unwrap(), &operators).unwrap();
+
+	let mut scope = Scope::default();
+	let result = loaded.iter().map(|expr| evaluator::evaluate(expr, &mut scope).unwrap()).last();
+	assert_eq!(result, Some(Value::from(40.0)));
+}
+
+#[test]
+fn testing() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+	let runner = TestRunner::new(operators);
+
+	let script = r#"
+define(counter, 0)
+define(increment, fn(set(counter, sum(counter, 1))))
+println("setup")
+
+# Each test starts from a fresh scope
+test("first", increment(), assert(equals(counter, 1), "counter is 1"), println("first"))
+test("second", increment(), assert(equals(counter, 1), "counter is still 1"))
+define(test_failing, fn(assert(equals(counter, 5), "counter is 5")))
+define(test_erroring, fn(undefined()))
+define(helper, fn(0))
+"#;
+
+	let results = runner.run(script, Some("setup\n")).unwrap();
+	let outcomes = results.iter().map(|r| (r.name.as_str(), r.passed())).collect::<Vec<_>>();
+	assert_eq!(
+		outcomes,
+		[("first", true), ("second", true), ("test_failing", false), ("test_erroring", false), ("expected output", true)]
+	);
+
+	// Output is captured per test, including the setup's
+	assert_eq!(results[0].output, "setup\nfirst\n");
+	assert_eq!(&script[results[0].span.clone()][..12], "test(\"first\"");
+	assert!(matches!(results[2].failure, Some(Failure::Error(EggError::AssertionFailed(_)))));
+	assert!(matches!(results[3].failure, Some(Failure::Error(EggError::FunctionNotFound(_)))));
+
+	// Output is compared with the expected output
+	let results = runner.run(script, Some("something else\n")).unwrap();
+	let Some(Failure::Output { expected }) = &results[4].failure else {
+		panic!("Expected the output to differ")
+	};
+	assert_eq!((expected.as_str(), results[4].output.as_str()), ("something else\n", "setup\n"));
+
+	assert!(runner.run("test(\"unbalanced\"", None).is_err());
+
+	// Tests read from an empty input rather than waiting on stdin
+	let results = runner.run("test(\"input\", assert(equals(readline(), \"\"), \"input is empty\"))", None).unwrap();
+	assert!(results[0].passed());
+}
+
+#[test]
+fn process() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+	operators::process(&mut operators, ["first", "second"]);
+
+	let run = |script: &str| {
+		let mut scope = Scope::default();
+		let mut result = Ok(Value::Nil);
+		for expr in parser::parse(script, &operators).unwrap() {
+			result = evaluator::evaluate(&expr, &mut scope);
+			if result.is_err() {
+				break;
+			}
+		}
+		result
+	};
+
+	// Arguments are an object keyed by position
+	assert_eq!(run("object.size(args())").unwrap(), Value::from(2.0));
+	assert_eq!(run("object.get(args(), 1)").unwrap(), Value::from("second"));
+
+	// Environment variables
+	assert!(matches!(run("env.get(\"PATH\")").unwrap(), Value::String(_)));
+	assert_eq!(run("env.get(\"EGG_SURELY_UNSET_VARIABLE\")").unwrap(), Value::Nil);
+	assert!(matches!(run("env.get(1)"), Err(EggError::OperatorComplaint(_))));
+
+	// Exiting unwinds out of functions, and stops the script
+	assert!(matches!(run("exit()"), Err(EggError::Exit(0))));
+	assert!(matches!(run("define(stop, fn(code, exit(code)))\nstop(4)\nundefined()"), Err(EggError::Exit(4))));
+	assert!(matches!(run("exit(\"1\")"), Err(EggError::OperatorComplaint(_))));
+}
+
+#[test]
+fn file_system() {
+	let root = std::env::temp_dir().join(format!("egg-fs-test-{}", std::process::id()));
+	std::fs::create_dir_all(root.join("nested")).unwrap();
+
+	let run = |sandbox: operators::Sandbox, script: &str| {
+		let mut operators = operators::empty();
+		operators::full(&mut operators);
+		operators::fs(&mut operators, sandbox);
+
+		let mut scope = Scope::default();
+		let mut result = Ok(Value::Nil);
+		for expr in parser::parse(script, &operators).unwrap() {
+			result = evaluator::evaluate(&expr, &mut scope);
+			if result.is_err() {
+				break;
+			}
+		}
+		result
+	};
+	let reason = |result: EggResult<Value>| match result {
+		Err(EggError::FileSystem { reason, .. }) => reason,
+		result => panic!("Expected a file system error, found: {result:?}"),
+	};
+
+	let sandbox = operators::Sandbox::read_write(&root);
+	let script = r#"
+fs.write("nested/notes.txt", "one ", 1)
+fs.append("nested/notes.txt", "two")
+fs.read("./nested/notes.txt")
+"#;
+	assert_eq!(run(sandbox.clone(), script).unwrap(), Value::from("one 1two"));
+	assert_eq!(run(sandbox.clone(), "fs.exists(\"nested/notes.txt\")").unwrap(), Value::Boolean(true));
+	assert_eq!(run(sandbox.clone(), "object.get(fs.list(\"nested\"), 0)").unwrap(), Value::from("notes.txt"));
+	assert_eq!(run(sandbox.clone(), "object.size(fs.list())").unwrap(), Value::from(1.0));
+
+	// Nothing outside of the root can be reached
+	let outside = root.parent().unwrap().join("outside.txt").display().to_string();
+	assert_eq!(reason(run(sandbox.clone(), "fs.read(\"../outside.txt\")")), FileSystemError::OutsideSandbox);
+	assert_eq!(reason(run(sandbox.clone(), "fs.exists(\"nested/../../outside.txt\")")), FileSystemError::OutsideSandbox);
+	assert_eq!(reason(run(sandbox.clone(), &format!("fs.write({outside:?}, 1)"))), FileSystemError::OutsideSandbox);
+	assert_eq!(reason(run(sandbox.clone(), "fs.read(\"missing.txt\")")), FileSystemError::NotFound);
+	assert!(matches!(run(sandbox.clone(), "fs.read(1)"), Err(EggError::OperatorComplaint(_))));
+
+	// Permissions
+	let read_only = operators::Sandbox::read_only(&root);
+	assert_eq!(reason(run(read_only.clone(), "fs.remove(\"nested/notes.txt\")")), FileSystemError::WriteDenied);
+	let write_only = operators::Sandbox { read: false, ..sandbox.clone() };
+	assert_eq!(reason(run(write_only, "fs.list()")), FileSystemError::ReadDenied);
+
+	assert_eq!(run(sandbox.clone(), "fs.remove(\"nested/notes.txt\")\nfs.remove(\"nested\")\nfs.exists(\"nested\")").unwrap(), Value::Boolean(false));
+	assert!(run(sandbox, "fs.remove(\".\")").is_err());
+	assert!(root.exists());
+
+	std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn console_io() {
+	use crate::io::{Input, InputBuffer, Output, OutputBuffer};
+
+	// A host defined output, recording each write separately
+	struct Writes(Rc<RefCell<Vec<String>>>);
+
+	impl Output for Writes {
+		fn write(&mut self, text: &str) -> EggResult<()> {
+			self.0.borrow_mut().push(text.to_string());
+			Ok(())
+		}
+	}
+
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+	let script = parser::parse("print(\"name?\")\ndefine(name, readline())\nprintln(\"Hello \", name)\nreadline(\"again? \")", &operators).unwrap();
+
+	let output = OutputBuffer::default();
+	let mut scope = Scope::default();
+	assert!(scope.set_output(Box::new(output.clone())).is_none());
+	scope.set_input(Box::new(InputBuffer::new("  Egg \nsecond line")));
+
+	let results = script.iter().map(|expr| evaluator::evaluate(expr, &mut scope).unwrap()).collect::<Vec<_>>();
+	assert_eq!(results[3], Value::from("second line"));
+	assert_eq!(output.contents(), "name?Hello Egg\nagain? ");
+
+	// Input that has ended reads as empty
+	assert_eq!(evaluator::evaluate(&script[3], &mut scope).unwrap(), Value::from(""));
+
+	// Replacing the output returns the previous one
+	let writes = Rc::new(RefCell::new(Vec::new()));
+	assert!(scope.set_output(Box::new(Writes(writes.clone()))).is_some());
+	evaluator::evaluate(&script[2], &mut scope).unwrap();
+	assert_eq!(*writes.borrow(), ["Hello Egg\n"]);
+	assert_eq!(output.take(), "name?Hello Egg\nagain? again? ");
+	assert!(scope.take_output().is_some() && scope.take_output().is_none());
+
+	// Objects are followed by a newline
+	let captured = io::capture("define(o, object.new()) print(o, 1) println(o)", &operators, "");
+	assert_eq!(captured.output, "{}\n 1{}\n\n");
+
+	let mut input = InputBuffer::new("a\r\nb");
+	assert_eq!((input.read_line().unwrap(), input.read_line().unwrap(), input.read_line().unwrap()), (Some("a".into()), Some("b".into()), None));
+}
+
+#[test]
+fn try_and_throw() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+	operators::process(&mut operators, [""; 0]);
+
+	let run = |script: &str| {
+		let captured = io::capture(script, &operators, "");
+		(captured.output, captured.result)
+	};
+	let field = |name: &str| format!("fn(err, object.get(err, \"{name}\"))");
+
+	// Values pass through when nothing is raised, and the handler is never created
+	assert_eq!(run("try(sum(1, 2), undefined())").1.unwrap(), Value::from(3.0));
+
+	// Builtin errors are described by kind and message
+	assert_eq!(run(&format!("try(assert(false, \"nope\"), {})", field("kind"))).1.unwrap(), Value::from("AssertionFailed"));
+	assert_eq!(run(&format!("try(missing, {})", field("message"))).1.unwrap(), Value::from("Binding not found in current scope: missing"));
+	assert_eq!(run(&format!("try(string.slice(\"abc\", 2, 5), {})", field("kind"))).1.unwrap(), Value::from("OperatorComplaint"));
+
+	// The location is that of the innermost failing expression
+	let script = "try(do(1, sum(1, nope)), fn(err, object.get(object.get(err, \"location\"), \"start\")))";
+	assert_eq!(run(script).1.unwrap(), Value::from(script.find("nope").unwrap() as f32));
+
+	// Errors caught earlier, or left uncaught, don't take it's place
+	let script = "try(do(try(first, fn(err, 0)), second), fn(err, object.get(object.get(err, \"location\"), \"start\")))";
+	assert_eq!(run(script).1.unwrap(), Value::from(script.find("second").unwrap() as f32));
+
+	let mut scope = Scope::default();
+	let script = "first\ntry(second, fn(err, object.get(object.get(err, \"location\"), \"start\")))";
+	let ast = parser::parse(script, &operators).unwrap();
+	assert!(evaluator::evaluate(&ast[0], &mut scope).is_err());
+	assert_eq!(evaluator::evaluate(&ast[1], &mut scope).unwrap(), Value::from(script.find("second").unwrap() as f32));
+
+	// Thrown values unwind through functions, and are handed back as is
+	let script = r#"
+define(check, fn(n, if(less_than(n, 0), do(
+	define(err, object.new()),
+	object.insert(err, "input", n),
+	throw(err)
+), n)))
+define(safe, fn(n, try(check(n), fn(err, do(
+	println(object.get(err, "kind"), ": ", object.get(err, "message")),
+	object.get(object.get(err, "value"), "input")
+)))))
+sum(safe(5), safe(-3))
+"#;
+	let (output, result) = run(script);
+	assert_eq!(result.unwrap(), Value::from(2.0));
+	assert_eq!(output, "Thrown: Object\n");
+	assert_eq!(run(&format!("try(throw(\"bad input\"), {})", field("message"))).1.unwrap(), Value::from("bad input"));
+
+	// Uncaught throws, errors in handlers and exits are not caught
+	assert!(matches!(run("throw(1)").1, Err(EggError::Thrown(Value::Number(_)))));
+	assert!(matches!(run("try(throw(1), fn(err, throw(2)))").1, Err(EggError::Thrown(value)) if value == Value::from(2.0)));
+	assert!(matches!(run("try(exit(4), fn(err, 0))").1, Err(EggError::Exit(4))));
+	assert!(matches!(run("try(throw(1), 5)").1, Err(EggError::OperatorComplaint(_))));
+
+	let mut scope = Scope::default();
+	scope.set_limits(Limits { live_values: Some(0), ..Limits::default() });
+	let script = parser::parse("try(throw(1), fn(e, e))", &operators).unwrap();
+	assert!(matches!(evaluator::evaluate(&script[0], &mut scope), Err(EggError::MemoryLimitExceeded(_))));
+}
+
+#[test]
+fn script_panics() {
+	let mut operators = operators::empty();
+	operators::full(&mut operators);
+
+	// Panics are errors located at the `panic(...)` call, and printed output is kept
+	let script = "println(\"before\")\ndefine(fail, fn(code, panic(code)))\nfail(7)";
+	let captured = io::capture(script, &operators, "");
+	assert_eq!(captured.output, "before\n");
+	let Err(EggError::ScriptPanic { value, location: Some(location) }) = captured.result else {
+		panic!("Expected a script panic, found: {:?}", captured.result);
+	};
+	assert_eq!((value, &script[location]), (Value::from(7.0), "panic(code)"));
+
+	// They cannot be caught
+	let captured = io::capture("try(panic(\"fatal\"), fn(err, 0))", &operators, "");
+	assert!(matches!(captured.result, Err(EggError::ScriptPanic { value: Value::String(_), .. })));
+
+	// Hooks observe them once, before they propagate
+	struct Panics(Rc<RefCell<Vec<String>>>);
+
+	impl Hook for Panics {
+		fn on_panic(&mut self, value: &Value, location: Range<usize>, _: &mut Scope) {
+			self.0.borrow_mut().push(format!("{value} at {location:?}"));
+		}
+	}
+
+	let panics = Rc::new(RefCell::new(Vec::new()));
+	let mut scope = Scope::default();
+	scope.add_hook(Box::new(Panics(panics.clone())));
+
+	let script = "do(1, panic(\"oh no\"))";
+	let expressions = parser::parse(script, &operators).unwrap();
+	assert!(evaluator::evaluate(&expressions[0], &mut scope).is_err());
+	assert_eq!(*panics.borrow(), ["\"oh no\" at 6..20"]);
+
+	// Fatal restores panicking the host
+	let mut scope = Scope::default();
+	scope.add_hook(Box::new(crate::hooks::Fatal));
+	let unwound = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| evaluator::evaluate(&expressions[0], &mut scope)));
+	assert!(unwound.is_err());
 }